@@ -2,55 +2,225 @@ use bevy::prelude::*;
 use bevy::sprite::MaterialMesh2dBundle;
 use bevy::sprite::Mesh2dHandle;
 
-use crate::collider::Collider;
+use crate::bindings::{Action, Actions};
+use crate::brick::BrickPlugin;
+use crate::collider::{Collider, Surfaces};
+use crate::events::{BallLostEvent, MultiBallEvent};
+use crate::interpolation::Interpolated;
+use crate::paddle::{Paddle, PaddlePlugin, PADDLE_SIZE, PADDLE_Y_OFFSET};
+use crate::powerup::{ActiveEffects, PowerUpPlugin};
 use crate::schedule::InGameSet;
 use crate::state::GameState;
 use crate::wall;
 
 // We set the z-value of the ball to 1 so it renders on top in the case of overlapping sprites.
-const BALL_STARTING_POSITION: Vec3 = Vec3::new(50.0, -200.0, 1.0);
+pub const BALL_Z: f32 = 1.0;
 pub const BALL_DIAMETER: f32 = 20.0;
-const BALL_SPEED: f32 = 400.0;
-const INITIAL_BALL_DIRECTION: Vec2 = Vec2::new(0.5, 0.5);
+pub const BALL_SPEED: f32 = 400.0;
 const BALL_COLOR: Color = Color::rgb(1.0, 0.5, 0.5);
+const FIREBALL_COLOR: Color = Color::rgb(1.0, 0.4, 0.0);
+// Serve parameters
+// Angle from vertical when launched from the very end of the paddle
+const MAX_LAUNCH_ANGLE: f32 = std::f32::consts::FRAC_PI_3;
+// Radians per second of the served ball rolling along the paddle
+const SERVE_ROLL_SPEED: f32 = 1.5;
+// Seconds a ball caught by the sticky paddle waits before launching itself
+const STICKY_RELEASE_DELAY: f32 = 3.0;
+// Multi-ball parameters
+const MULTI_BALL_SPLIT: usize = 2;
+// Angle between the velocities of split balls
+const MULTI_BALL_SPREAD: f32 = 0.4;
+// Distance split balls start ahead of the ball they split from
+const MULTI_BALL_OFFSET: f32 = BALL_DIAMETER / 2.0;
+const MAX_BALLS: usize = 12;
 
 pub struct BallPlugin;
 
 impl Plugin for BallPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(
-            Update,
-            BallPlugin::move_ball.in_set(InGameSet::EntityUpdates),
+            FixedUpdate,
+            BallPlugin::follow_paddle
+                .after(PaddlePlugin::update_paddle)
+                .in_set(InGameSet::EntityUpdates),
+        )
+        .add_systems(
+            FixedUpdate,
+            BallPlugin::update_fireball.in_set(InGameSet::EntityUpdates),
+        )
+        .add_systems(
+            FixedUpdate,
+            BallPlugin::split_balls
+                .after(PowerUpPlugin::collect)
+                .in_set(InGameSet::CollisionResponse),
+        )
+        .add_systems(Update, BallPlugin::launch_ball.in_set(InGameSet::UserInput))
+        .add_systems(OnEnter(GameState::PrepGame), BallPlugin::serve_ball)
+        .add_systems(
+            OnTransition {
+                from: GameState::BallLost,
+                to: GameState::InGame,
+            },
+            BallPlugin::serve_ball,
         )
-        .add_systems(OnEnter(GameState::PrepGame), BallPlugin::spawn_ball)
         .add_systems(OnEnter(GameState::MainMenu), BallPlugin::despawn_all_balls)
         .add_systems(OnExit(GameState::EndMenu), BallPlugin::despawn_all_balls)
         .add_systems(
-            Update,
-            BallPlugin::check_end_game.in_set(InGameSet::CheckGameStatus),
+            OnExit(GameState::LevelCleared),
+            BallPlugin::despawn_all_balls,
         )
         .add_systems(
-            Update,
+            FixedUpdate,
+            BallPlugin::check_end_game
+                .after(BrickPlugin::check_win)
+                .in_set(InGameSet::CheckGameStatus),
+        )
+        .add_systems(
+            FixedUpdate,
             BallPlugin::handle_ball_loss.in_set(InGameSet::DespawnEntities),
         );
     }
 }
 
 impl BallPlugin {
-    fn move_ball(mut query: Query<(&mut Transform, &mut Ball)>, time: Res<Time>) {
-        for (mut transform, ball) in &mut query {
-            transform.translation.x += ball.velocity.x * time.delta_seconds();
-            transform.translation.y += ball.velocity.y * time.delta_seconds();
+    // Spawn a ball resting on the paddle, the paddle may not exist yet on a new level
+    fn serve_ball(
+        mut commands: Commands,
+        mut meshes: ResMut<Assets<Mesh>>,
+        mut materials: ResMut<Assets<ColorMaterial>>,
+        paddle_query: Query<&Transform, With<Paddle>>,
+    ) {
+        let paddle_x = paddle_query
+            .get_single()
+            .map_or(0.0, |transform| transform.translation.x);
+        let position = Vec3 {
+            x: paddle_x,
+            y: PADDLE_Y_OFFSET + PADDLE_SIZE.y / 2.0 + BALL_DIAMETER / 2.0,
+            z: BALL_Z,
+        };
+        commands.spawn((
+            BallBundle::new(&mut meshes, &mut materials, position),
+            AttachedToPaddle::served(),
+        ));
+    }
+
+    // Keep attached balls on top of the paddle, served balls roll along it
+    // and caught balls launch themselves once they have been held too long
+    fn follow_paddle(
+        mut commands: Commands,
+        mut ball_query: Query<
+            (Entity, &mut Ball, &mut Transform, &mut AttachedToPaddle),
+            Without<Paddle>,
+        >,
+        paddle_query: Query<(&Transform, &Collider), With<Paddle>>,
+        time: Res<Time>,
+    ) {
+        let Ok((paddle_transform, paddle_collider)) = paddle_query.get_single() else {
+            return;
+        };
+        // The paddle can be resized by power-ups
+        let (min, max) = paddle_collider.bounds(paddle_transform);
+        let max_offset = ((max.x - min.x) / 2.0 - BALL_DIAMETER / 2.0).max(0.0);
+        for (entity, mut ball, mut transform, mut attached) in &mut ball_query {
+            attached.elapsed += time.delta_seconds();
+            match attached.release.as_mut() {
+                None => attached.offset = (attached.elapsed * SERVE_ROLL_SPEED).sin(),
+                Some(release) => {
+                    if release.tick(time.delta()).just_finished() {
+                        BallPlugin::launch(&mut commands, entity, &mut ball, &attached);
+                    }
+                }
+            }
+            transform.translation.x = paddle_transform.translation.x + attached.offset * max_offset;
+            transform.translation.y =
+                paddle_transform.translation.y + PADDLE_SIZE.y / 2.0 + BALL_DIAMETER / 2.0;
         }
     }
 
-    // Spawn a ball
-    fn spawn_ball(
+    // Release attached balls at an angle set by where they rest on the paddle
+    fn launch_ball(
         mut commands: Commands,
-        meshes: ResMut<Assets<Mesh>>,
-        materials: ResMut<Assets<ColorMaterial>>,
+        actions: Actions,
+        mut ball_query: Query<(Entity, &mut Ball, &AttachedToPaddle)>,
     ) {
-        commands.spawn(BallBundle::new(meshes, materials));
+        if !actions.just_pressed(Action::Launch) {
+            return;
+        }
+        for (entity, mut ball, attached) in &mut ball_query {
+            BallPlugin::launch(&mut commands, entity, &mut ball, attached);
+        }
+    }
+
+    fn launch(
+        commands: &mut Commands,
+        entity: Entity,
+        ball: &mut Ball,
+        attached: &AttachedToPaddle,
+    ) {
+        ball.velocity = Ball::launch_direction(attached.offset) * BALL_SPEED;
+        commands.entity(entity).remove::<AttachedToPaddle>();
+    }
+
+    // Turn every ball into a fireball while the effect runs, and back after
+    #[allow(clippy::type_complexity)]
+    fn update_fireball(
+        mut commands: Commands,
+        ball_query: Query<(Entity, &Handle<ColorMaterial>, Has<Fireball>), With<Ball>>,
+        mut materials: ResMut<Assets<ColorMaterial>>,
+        active_effects: Res<ActiveEffects>,
+    ) {
+        let fireball = active_effects.has_fireball();
+        for (entity, material, is_fireball) in &ball_query {
+            if is_fireball == fireball {
+                continue;
+            }
+            if fireball {
+                commands.entity(entity).insert(Fireball);
+            } else {
+                commands.entity(entity).remove::<Fireball>();
+            }
+            if let Some(material) = materials.get_mut(material) {
+                material.color = if fireball { FIREBALL_COLOR } else { BALL_COLOR };
+            }
+        }
+    }
+
+    // Each free ball is joined by copies heading off at diverging angles,
+    // placed a little way along their own path so they do not start on top of it
+    fn split_balls(
+        mut commands: Commands,
+        mut meshes: ResMut<Assets<Mesh>>,
+        mut materials: ResMut<Assets<ColorMaterial>>,
+        mut multi_ball_events: EventReader<MultiBallEvent>,
+        ball_query: Query<(&Ball, &Transform), Without<AttachedToPaddle>>,
+        all_balls_query: Query<(), With<Ball>>,
+        surfaces: Surfaces,
+    ) {
+        let mut ball_count = all_balls_query.iter().count();
+        for _ in 0..multi_ball_events.read().count() {
+            for (ball, transform) in &ball_query {
+                for split in 1..=MULTI_BALL_SPLIT {
+                    if ball_count >= MAX_BALLS {
+                        return;
+                    }
+                    // Alternate sides, fanning further out with every copy
+                    let side = if split % 2 == 0 { 1.0 } else { -1.0 };
+                    let angle = side * MULTI_BALL_SPREAD * split.div_ceil(2) as f32;
+                    let velocity = Vec2::from_angle(angle).rotate(ball.velocity);
+                    let offset = surfaces.clamp_motion(
+                        transform.translation.truncate(),
+                        velocity.normalize_or_zero() * MULTI_BALL_OFFSET,
+                        BALL_DIAMETER / 2.0,
+                    );
+                    let position = transform.translation + offset.extend(0.0);
+                    commands.spawn(
+                        BallBundle::new(&mut meshes, &mut materials, position)
+                            .with_velocity(velocity),
+                    );
+                    ball_count += 1;
+                }
+            }
+        }
     }
 
     // Despawn all balls
@@ -60,9 +230,10 @@ impl BallPlugin {
         }
     }
 
-    fn handle_ball_loss(
+    pub fn handle_ball_loss(
         mut commands: Commands,
         ball_query: Query<(Entity, &Transform), With<Ball>>,
+        mut ball_lost_events: EventWriter<BallLostEvent>,
     ) {
         for (ball_entity, transform) in &ball_query {
             if transform.translation.y > wall::TOP_WALL
@@ -71,13 +242,19 @@ impl BallPlugin {
                 || transform.translation.x > wall::RIGHT_WALL
             {
                 commands.entity(ball_entity).despawn();
+                ball_lost_events.send(BallLostEvent);
             }
         }
     }
 
+    // Losing the last ball costs a life, unless the level was cleared on the same tick
     fn check_end_game(ball_query: Query<&Ball>, mut next_state: ResMut<NextState<GameState>>) {
-        if ball_query.is_empty() {
-            next_state.set(GameState::EndMenu);
+        let level_ended = matches!(
+            next_state.0,
+            Some(GameState::LevelCleared | GameState::EndMenu)
+        );
+        if ball_query.is_empty() && !level_ended {
+            next_state.set(GameState::BallLost);
         }
     }
 }
@@ -85,36 +262,131 @@ impl BallPlugin {
 #[derive(Component)]
 pub struct Ball {
     pub velocity: Vec2,
-    pub last_col_entity: Entity,
+}
+
+impl Ball {
+    // Straight up from the paddle centre, leaning out towards the ends
+    pub fn launch_direction(offset: f32) -> Vec2 {
+        let angle = offset.clamp(-1.0, 1.0) * MAX_LAUNCH_ANGLE;
+        Vec2::new(angle.sin(), angle.cos())
+    }
+}
+
+// Ball that breaks bricks in one hit and flies through them
+#[derive(Component)]
+pub struct Fireball;
+
+// Ball resting on the paddle, waiting to be launched
+#[derive(Component)]
+pub struct AttachedToPaddle {
+    // Place along the paddle, from -1 at the left end to 1 at the right end
+    pub offset: f32,
+    elapsed: f32,
+    // Set for balls caught by the sticky paddle, which launch when it finishes
+    release: Option<Timer>,
+}
+
+impl AttachedToPaddle {
+    // Rolls along the paddle until the player launches it
+    pub fn served() -> AttachedToPaddle {
+        AttachedToPaddle {
+            offset: 0.0,
+            elapsed: 0.0,
+            release: None,
+        }
+    }
+
+    // Held where it landed until launched or released
+    pub fn caught(offset: f32) -> AttachedToPaddle {
+        AttachedToPaddle {
+            offset: offset.clamp(-1.0, 1.0),
+            elapsed: 0.0,
+            release: Some(Timer::from_seconds(STICKY_RELEASE_DELAY, TimerMode::Once)),
+        }
+    }
 }
 
 #[derive(Bundle)]
-struct BallBundle {
+pub struct BallBundle {
     mesh: MaterialMesh2dBundle<ColorMaterial>,
     ball: Ball,
     collider: Collider,
+    interpolated: Interpolated,
 }
 
 impl BallBundle {
     pub fn new(
-        mut meshes: ResMut<Assets<Mesh>>,
-        mut materials: ResMut<Assets<ColorMaterial>>,
+        meshes: &mut Assets<Mesh>,
+        materials: &mut Assets<ColorMaterial>,
+        position: Vec3,
     ) -> BallBundle {
         BallBundle {
             mesh: MaterialMesh2dBundle {
-                mesh: Mesh2dHandle(meshes.add(Circle::new(BALL_DIAMETER/2.0))),
+                mesh: Mesh2dHandle(meshes.add(Circle::new(BALL_DIAMETER / 2.0))),
                 material: materials.add(ColorMaterial {
                     color: BALL_COLOR,
                     ..Default::default()
                 }),
-                transform: Transform::from_translation(BALL_STARTING_POSITION),
+                transform: Transform::from_translation(position),
                 ..default()
             },
             ball: Ball {
-                velocity: INITIAL_BALL_DIRECTION.normalize() * BALL_SPEED,
-                last_col_entity: Entity::PLACEHOLDER,
+                velocity: Vec2::ZERO,
             },
-            collider: Collider,
+            collider: Collider::Circle {
+                radius: BALL_DIAMETER / 2.0,
+            },
+            interpolated: Interpolated::new(position),
+        }
+    }
+
+    pub fn with_velocity(mut self, velocity: Vec2) -> BallBundle {
+        self.ball.velocity = velocity;
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::RunSystemOnce;
+
+    use super::*;
+    use crate::brick::{Brick, BrickKinds, LOW_KIND};
+    use crate::level::{Campaign, CampaignHandle, CurrentLevel};
+
+    // Second to last level of a two level campaign, with no balls in play
+    fn world(bricks: usize) -> World {
+        let mut world = World::new();
+        let mut campaigns = Assets::<Campaign>::default();
+        let campaign = campaigns.add(Campaign {
+            levels: vec![Handle::default(), Handle::default()],
+        });
+        world.insert_resource(campaigns);
+        world.insert_resource(CampaignHandle(campaign));
+        world.insert_resource(CurrentLevel(0));
+        world.init_resource::<NextState<GameState>>();
+        let kinds = BrickKinds::default();
+        for _ in 0..bricks {
+            world.spawn(Brick::new(kinds.id(LOW_KIND).unwrap(), &kinds));
         }
+        world
+    }
+
+    fn check_game_status(world: &mut World) -> Option<GameState> {
+        world.run_system_once(BrickPlugin::check_win);
+        world.run_system_once(BallPlugin::check_end_game);
+        world.resource::<NextState<GameState>>().0.clone()
+    }
+
+    #[test]
+    fn last_brick_and_last_ball_on_the_same_tick_clear_the_level() {
+        let mut world = world(0);
+        assert_eq!(check_game_status(&mut world), Some(GameState::LevelCleared));
+    }
+
+    #[test]
+    fn last_ball_lost_with_bricks_left() {
+        let mut world = world(1);
+        assert_eq!(check_game_status(&mut world), Some(GameState::BallLost));
     }
 }
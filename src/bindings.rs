@@ -0,0 +1,405 @@
+use std::collections::{BTreeMap, HashMap};
+
+use bevy::ecs::system::SystemParam;
+use bevy::input::keyboard::{Key, KeyboardInput};
+use bevy::input::{ButtonState, InputSystem};
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::state::GameState;
+use crate::storage::{self, StorageError};
+
+const BINDINGS_FILE: &str = "bindings.ron";
+
+// What the player can do, whatever key they chose for it
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Action {
+    MoveLeft,
+    MoveRight,
+    // Launches held balls and fires the laser
+    Launch,
+    Pause,
+    MenuConfirm,
+}
+
+impl Action {
+    pub const ALL: [Action; 5] = [
+        Action::MoveLeft,
+        Action::MoveRight,
+        Action::Launch,
+        Action::Pause,
+        Action::MenuConfirm,
+    ];
+
+    fn default_key(self) -> KeyCode {
+        match self {
+            Action::MoveLeft => KeyCode::ArrowLeft,
+            Action::MoveRight => KeyCode::ArrowRight,
+            Action::Launch => KeyCode::Space,
+            Action::Pause => KeyCode::Escape,
+            Action::MenuConfirm => KeyCode::Enter,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Action::MoveLeft => "Move Left",
+            Action::MoveRight => "Move Right",
+            Action::Launch => "Launch / Fire",
+            Action::Pause => "Pause",
+            Action::MenuConfirm => "Confirm",
+        }
+    }
+}
+
+// Why a key could not be bound to an action
+#[derive(Debug, Error, Clone, Copy, PartialEq, Eq)]
+pub enum BindError {
+    #[error("already used by {}", .action.label())]
+    Taken { key: KeyCode, action: Action },
+    // Confirming on the end screen would also type into the name field
+    #[error("used to type names, it cannot confirm")]
+    EditsText { key: KeyCode },
+}
+
+// Key bound to each action, kept in the platform config directory.
+// Physical keys are stored so bindings survive a keyboard layout change.
+#[derive(Resource, Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Bindings {
+    keys: BTreeMap<Action, KeyCode>,
+}
+
+impl Default for Bindings {
+    fn default() -> Self {
+        Bindings {
+            keys: Action::ALL
+                .into_iter()
+                .map(|action| (action, action.default_key()))
+                .collect(),
+        }
+    }
+}
+
+impl Bindings {
+    fn load() -> Result<Bindings, StorageError> {
+        let bindings: Bindings = storage::load(&storage::config_file(BINDINGS_FILE)?)?;
+        Ok(bindings.checked())
+    }
+
+    // Keys from a file go through the same checks as the controls screen, as it may
+    // have been edited by hand. Defaults are used if that leaves an action without a key.
+    fn checked(self) -> Bindings {
+        let mut bindings = Bindings {
+            keys: BTreeMap::new(),
+        };
+        for (action, key) in self.keys {
+            if let Err(error) = bindings.bind(action, key) {
+                warn!("Ignoring {:?} for {}: {}", key, action.label(), error);
+            }
+        }
+        // Actions missing from the file get their default key, unless it is taken
+        for action in Action::ALL {
+            if !bindings.keys.contains_key(&action) {
+                let _ = bindings.bind(action, action.default_key());
+            }
+        }
+        if let Some(action) = Action::ALL
+            .into_iter()
+            .find(|action| !bindings.keys.contains_key(action))
+        {
+            warn!("Using default key bindings, {} has no key", action.label());
+            return Bindings::default();
+        }
+        bindings
+    }
+
+    fn save(&self) -> Result<(), StorageError> {
+        storage::save(&storage::config_file(BINDINGS_FILE)?, self)
+    }
+
+    pub fn key(&self, action: Action) -> Option<KeyCode> {
+        self.keys.get(&action).copied()
+    }
+
+    // Action a key is bound to
+    pub fn action_for(&self, key: KeyCode) -> Option<Action> {
+        self.keys
+            .iter()
+            .find(|(_, bound)| **bound == key)
+            .map(|(action, _)| *action)
+    }
+
+    // Bind a key to an action, unless another action already uses it
+    pub fn bind(&mut self, action: Action, key: KeyCode) -> Result<(), BindError> {
+        if action == Action::MenuConfirm && edits_text(key) {
+            return Err(BindError::EditsText { key });
+        }
+        match self.action_for(key) {
+            Some(other) if other != action => Err(BindError::Taken { key, action: other }),
+            _ => {
+                self.keys.insert(action, key);
+                Ok(())
+            }
+        }
+    }
+}
+
+// Keys that type or delete a character in a text field
+fn edits_text(key: KeyCode) -> bool {
+    matches!(
+        key,
+        KeyCode::KeyA
+            | KeyCode::KeyB
+            | KeyCode::KeyC
+            | KeyCode::KeyD
+            | KeyCode::KeyE
+            | KeyCode::KeyF
+            | KeyCode::KeyG
+            | KeyCode::KeyH
+            | KeyCode::KeyI
+            | KeyCode::KeyJ
+            | KeyCode::KeyK
+            | KeyCode::KeyL
+            | KeyCode::KeyM
+            | KeyCode::KeyN
+            | KeyCode::KeyO
+            | KeyCode::KeyP
+            | KeyCode::KeyQ
+            | KeyCode::KeyR
+            | KeyCode::KeyS
+            | KeyCode::KeyT
+            | KeyCode::KeyU
+            | KeyCode::KeyV
+            | KeyCode::KeyW
+            | KeyCode::KeyX
+            | KeyCode::KeyY
+            | KeyCode::KeyZ
+            | KeyCode::Digit0
+            | KeyCode::Digit1
+            | KeyCode::Digit2
+            | KeyCode::Digit3
+            | KeyCode::Digit4
+            | KeyCode::Digit5
+            | KeyCode::Digit6
+            | KeyCode::Digit7
+            | KeyCode::Digit8
+            | KeyCode::Digit9
+            | KeyCode::Numpad0
+            | KeyCode::Numpad1
+            | KeyCode::Numpad2
+            | KeyCode::Numpad3
+            | KeyCode::Numpad4
+            | KeyCode::Numpad5
+            | KeyCode::Numpad6
+            | KeyCode::Numpad7
+            | KeyCode::Numpad8
+            | KeyCode::Numpad9
+            | KeyCode::NumpadAdd
+            | KeyCode::NumpadComma
+            | KeyCode::NumpadDecimal
+            | KeyCode::NumpadDivide
+            | KeyCode::NumpadEqual
+            | KeyCode::NumpadHash
+            | KeyCode::NumpadMultiply
+            | KeyCode::NumpadParenLeft
+            | KeyCode::NumpadParenRight
+            | KeyCode::NumpadStar
+            | KeyCode::NumpadSubtract
+            | KeyCode::Space
+            | KeyCode::Backquote
+            | KeyCode::Backslash
+            | KeyCode::BracketLeft
+            | KeyCode::BracketRight
+            | KeyCode::Comma
+            | KeyCode::Equal
+            | KeyCode::Minus
+            | KeyCode::Period
+            | KeyCode::Quote
+            | KeyCode::Semicolon
+            | KeyCode::Slash
+            | KeyCode::IntlBackslash
+            | KeyCode::IntlRo
+            | KeyCode::IntlYen
+            | KeyCode::Backspace
+            | KeyCode::NumpadBackspace
+    )
+}
+
+// Keyboard state read through the bindings
+#[derive(SystemParam)]
+pub struct Actions<'w> {
+    keyboard_input: Res<'w, ButtonInput<KeyCode>>,
+    bindings: Res<'w, Bindings>,
+}
+
+impl Actions<'_> {
+    pub fn pressed(&self, action: Action) -> bool {
+        self.bindings
+            .key(action)
+            .is_some_and(|key| self.keyboard_input.pressed(key))
+    }
+
+    pub fn just_pressed(&self, action: Action) -> bool {
+        self.bindings
+            .key(action)
+            .is_some_and(|key| self.keyboard_input.just_pressed(key))
+    }
+}
+
+// What the current keyboard layout prints on each physical key, learnt as keys are
+// pressed. Bindings keep the physical key, only the screens show these.
+#[derive(Resource, Default)]
+pub struct KeyNames(HashMap<KeyCode, String>);
+
+impl KeyNames {
+    // Short name for the screens. Keys not pressed yet fall back to the physical
+    // key, KeyA reads as A.
+    pub fn name(&self, key: KeyCode) -> String {
+        if let Some(name) = self.0.get(&key) {
+            return name.clone();
+        }
+        let name = format!("{:?}", key);
+        name.strip_prefix("Key")
+            .or_else(|| name.strip_prefix("Digit"))
+            .unwrap_or(&name)
+            .to_string()
+    }
+
+    fn record(&mut self, key: KeyCode, logical_key: &Key) {
+        let name = match logical_key {
+            Key::Character(character) => character.to_uppercase(),
+            Key::Dead(Some(character)) => character.to_uppercase().to_string(),
+            // Named keys read the same on every layout
+            _ => return,
+        };
+        if !name.trim().is_empty() {
+            self.0.insert(key, name);
+        }
+    }
+}
+
+pub struct BindingsPlugin;
+
+impl Plugin for BindingsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Bindings>()
+            .init_resource::<KeyNames>()
+            .add_systems(Startup, BindingsPlugin::load_bindings)
+            .add_systems(
+                PreUpdate,
+                BindingsPlugin::record_key_names.after(InputSystem),
+            )
+            .add_systems(OnExit(GameState::Controls), BindingsPlugin::save_bindings);
+    }
+}
+
+impl BindingsPlugin {
+    // A missing or broken file keeps the default keys
+    fn load_bindings(mut bindings: ResMut<Bindings>) {
+        match Bindings::load() {
+            Ok(loaded) => *bindings = loaded,
+            Err(error) if error.is_missing() => (),
+            Err(error) => warn!("Using default key bindings: {}", error),
+        }
+    }
+
+    // Shift and AltGr change what a key types, only bare presses name it
+    fn record_key_names(
+        mut keyboard_events: EventReader<KeyboardInput>,
+        keyboard_input: Res<ButtonInput<KeyCode>>,
+        mut key_names: ResMut<KeyNames>,
+    ) {
+        let modified = keyboard_input.any_pressed([
+            KeyCode::ShiftLeft,
+            KeyCode::ShiftRight,
+            KeyCode::AltRight,
+        ]);
+        for event in keyboard_events.read() {
+            if event.state == ButtonState::Pressed && !modified {
+                key_names.record(event.key_code, &event.logical_key);
+            }
+        }
+    }
+
+    fn save_bindings(bindings: Res<Bindings>) {
+        if let Err(error) = bindings.save() {
+            error!("Could not save key bindings: {}", error);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bindings(keys: &[(Action, KeyCode)]) -> Bindings {
+        Bindings {
+            keys: keys.iter().copied().collect(),
+        }
+    }
+
+    #[test]
+    fn taken_key_is_refused() {
+        let mut bindings = Bindings::default();
+        assert_eq!(
+            bindings.bind(Action::Launch, KeyCode::ArrowLeft),
+            Err(BindError::Taken {
+                key: KeyCode::ArrowLeft,
+                action: Action::MoveLeft
+            })
+        );
+        assert_eq!(bindings.key(Action::Launch), Some(KeyCode::Space));
+    }
+
+    #[test]
+    fn confirm_cannot_take_a_typing_key() {
+        let mut bindings = Bindings::default();
+        assert_eq!(
+            bindings.bind(Action::MenuConfirm, KeyCode::KeyA),
+            Err(BindError::EditsText { key: KeyCode::KeyA })
+        );
+        assert_eq!(bindings.bind(Action::MenuConfirm, KeyCode::Tab), Ok(()));
+        assert_eq!(bindings.bind(Action::Pause, KeyCode::KeyP), Ok(()));
+    }
+
+    #[test]
+    fn missing_actions_get_their_default_key() {
+        let loaded = bindings(&[(Action::Launch, KeyCode::ArrowUp)]).checked();
+        assert_eq!(loaded.key(Action::Launch), Some(KeyCode::ArrowUp));
+        assert_eq!(loaded.key(Action::MoveLeft), Some(KeyCode::ArrowLeft));
+        assert_eq!(loaded.key(Action::MenuConfirm), Some(KeyCode::Enter));
+    }
+
+    #[test]
+    fn duplicate_key_falls_back_to_defaults() {
+        // Pause loses the key to Launch and its own default is taken by Move Left
+        let loaded = bindings(&[
+            (Action::MoveLeft, KeyCode::Escape),
+            (Action::MoveRight, KeyCode::ArrowRight),
+            (Action::Launch, KeyCode::Space),
+            (Action::Pause, KeyCode::Space),
+            (Action::MenuConfirm, KeyCode::Enter),
+        ])
+        .checked();
+        assert_eq!(loaded, Bindings::default());
+    }
+
+    #[test]
+    fn keys_are_named_by_the_layout() {
+        // Q on the keyboard types A on an AZERTY layout
+        let mut key_names = KeyNames::default();
+        key_names.record(KeyCode::KeyQ, &Key::Character("a".into()));
+        key_names.record(KeyCode::Space, &Key::Space);
+        assert_eq!(key_names.name(KeyCode::KeyQ), "A");
+        assert_eq!(key_names.name(KeyCode::Space), "Space");
+        assert_eq!(key_names.name(KeyCode::KeyW), "W");
+        assert_eq!(key_names.name(KeyCode::Digit1), "1");
+    }
+
+    #[test]
+    fn typing_confirm_key_from_file_is_replaced() {
+        let loaded = bindings(&[(Action::MenuConfirm, KeyCode::KeyE)]).checked();
+        assert_eq!(loaded.key(Action::MenuConfirm), Some(KeyCode::Enter));
+    }
+}
@@ -1,138 +1,727 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
 use bevy::prelude::*;
 
+use crate::ball::{Ball, Fireball};
+use crate::broadphase::BroadPhase;
 use crate::collider::Collider;
-use crate::events::UpdateScoreEvent;
+use crate::combo::Combo;
+use crate::events::{BrickDestroyedEvent, CollisionEvent, CollisionKind, UpdateScoreEvent};
+use crate::formation::{Formation, FormationMember};
+use crate::interpolation::Interpolated;
+use crate::level::{BrickRole, Campaign, CampaignHandle, CurrentLevel, Level};
+use crate::rng::GameRng;
 use crate::schedule::InGameSet;
 use crate::state::GameState;
 
-// Brick spawn parameters
-const BRICK_SIZE: Vec3 = Vec3::new(100., 30., 1.0);
-pub const FIRST_ROW_BRICK_Y: f32 = 300.0;
-pub const GAP_BETWEEN_BRICK_ROW: f32 = 60.0;
-pub const GAP_BETWEEN_BRICK_COL: f32 = 120.0;
+// Brick size
+pub const BRICK_SIZE: Vec3 = Vec3::new(100., 30., 1.0);
 
 // Brick colors
 pub const BRICK_LOW_COLOR: Color = Color::rgb(0.5, 0.5, 1.0);
 pub const BRICK_MID_COLOR: Color = Color::rgb(1.0, 0.5, 0.5);
 pub const BRICK_HIGH_COLOR: Color = Color::rgb(0.8, 0.8, 0.0);
 pub const BRICK_SUPER_COLOR: Color = Color::rgb(0.4, 0.4, 0.5);
+// Colours of the built-in kinds, by hit points left
+const DURABILITY_COLORS: [Color; 4] = [
+    BRICK_LOW_COLOR,
+    BRICK_MID_COLOR,
+    BRICK_HIGH_COLOR,
+    BRICK_SUPER_COLOR,
+];
+// Cell symbols of the built-in kinds
+pub const LOW_KIND: char = 'L';
+pub const MID_KIND: char = 'M';
+pub const HIGH_KIND: char = 'H';
+pub const SUPER_KIND: char = 'S';
+// Built-in kinds: cell symbol, hit points and score
+const DEFAULT_KINDS: [(char, u32, u32); 4] = [
+    (LOW_KIND, 1, 1),
+    (MID_KIND, 2, 2),
+    (HIGH_KIND, 3, 3),
+    (SUPER_KIND, 4, 5),
+];
+pub const BRICK_EXPLOSIVE_COLOR: Color = Color::rgb(1.0, 0.6, 0.1);
+pub const BRICK_STEEL_COLOR: Color = Color::rgb(0.7, 0.7, 0.75);
+pub const BRICK_KEY_COLOR: Color = Color::rgb(1.0, 0.85, 0.2);
+pub const BRICK_HIVE_COLOR: Color = Color::rgb(0.6, 0.3, 0.8);
+// Regenerating bricks show their level color blended with this one
+const REGENERATING_TINT: Color = Color::rgb(0.2, 0.9, 0.4);
+// Locked bricks show their level color darkened
+const LOCKED_BRIGHTNESS: f32 = 0.35;
+// Explosive, key and hive bricks darken down to this with one hit point left
+const DAMAGED_BRIGHTNESS: f32 = 0.55;
+
+// Default reach of an exploding brick, enough for its direct neighbours
+pub const EXPLOSION_RADIUS: f32 = 140.0;
+// Default seconds a regenerating brick must go unhurt to heal one level
+pub const REGENERATION_DELAY: f32 = 4.0;
+// Default seconds between two bricks from a hive
+pub const HIVE_INTERVAL: f32 = 6.0;
 
 pub struct BrickPlugin;
 
 impl Plugin for BrickPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(OnEnter(GameState::PrepGame), BrickPlugin::spawn_brick)
+        app.init_resource::<BrickGrid>()
+            .init_resource::<BrickKinds>()
+            .add_systems(OnEnter(GameState::PrepGame), BrickPlugin::spawn_brick)
             .add_systems(OnEnter(GameState::MainMenu), BrickPlugin::despawn_all_brick)
             .add_systems(OnExit(GameState::EndMenu), BrickPlugin::despawn_all_brick)
             .add_systems(
-                Update,
-                BrickPlugin::update_brick_color.in_set(InGameSet::CollisionDetection),
+                OnExit(GameState::LevelCleared),
+                BrickPlugin::despawn_all_brick,
+            )
+            .add_systems(
+                FixedUpdate,
+                BrickPlugin::regenerate_bricks.in_set(InGameSet::EntityUpdates),
+            )
+            .add_systems(
+                FixedUpdate,
+                (BrickPlugin::damage_brick, BrickPlugin::update_brick_color)
+                    .chain()
+                    .in_set(InGameSet::CollisionResponse),
             )
             .add_systems(
-                Update,
-                BrickPlugin::despawn_brick.in_set(InGameSet::DespawnEntities),
+                FixedUpdate,
+                (
+                    BrickPlugin::explode_bricks,
+                    BrickPlugin::unlock_bricks,
+                    BrickPlugin::despawn_brick,
+                    BrickPlugin::grow_hives,
+                )
+                    .chain()
+                    .in_set(InGameSet::DespawnEntities),
             )
             .add_systems(
-                Update,
+                FixedUpdate,
                 BrickPlugin::check_win.in_set(InGameSet::CheckGameStatus),
             );
     }
 }
 
 impl BrickPlugin {
-    // Spawn brick at start up
-    fn spawn_brick(mut commands: Commands) {
-        // Setup brick
-        for col in -2..3 {
-            let x = col as f32 * GAP_BETWEEN_BRICK_COL;
-            let y = FIRST_ROW_BRICK_Y - GAP_BETWEEN_BRICK_ROW * 0 as f32;
-            let brick_location = Vec3 { x, y, z: 0.0 };
-            commands.spawn(BrickBundle::new(BrickLevel::SUPER, brick_location));
-        }
-        for col in -2..3 {
-            let x = col as f32 * GAP_BETWEEN_BRICK_COL;
-            let y = FIRST_ROW_BRICK_Y - GAP_BETWEEN_BRICK_ROW * 1 as f32;
-            let brick_location = Vec3 { x, y, z: 0.0 };
-            commands.spawn(BrickBundle::new(BrickLevel::HIGH, brick_location));
-        }
-        for col in -2..3 {
-            let x = col as f32 * GAP_BETWEEN_BRICK_COL;
-            let y = FIRST_ROW_BRICK_Y - GAP_BETWEEN_BRICK_ROW * 2 as f32;
-            let brick_location = Vec3 { x, y, z: 0.0 };
-            commands.spawn(BrickBundle::new(BrickLevel::MID, brick_location));
-        }
-        for col in -2..3 {
-            let x = col as f32 * GAP_BETWEEN_BRICK_COL;
-            let y = FIRST_ROW_BRICK_Y - GAP_BETWEEN_BRICK_ROW * 3 as f32;
-            let brick_location = Vec3 { x, y, z: 0.0 };
-            commands.spawn(BrickBundle::new(BrickLevel::LOW, brick_location));
-        }
-    }
-
-    fn update_brick_color(mut query: Query<(&Brick, &mut Sprite)>) {
-        for (brick, mut sprite) in &mut query {
-            sprite.color = match brick.level {
-                BrickLevel::NONE => BRICK_LOW_COLOR,
-                BrickLevel::LOW => BRICK_LOW_COLOR,
-                BrickLevel::MID => BRICK_MID_COLOR,
-                BrickLevel::HIGH => BRICK_HIGH_COLOR,
-                BrickLevel::SUPER => BRICK_SUPER_COLOR,
+    // Spawn bricks from the current campaign level
+    fn spawn_brick(
+        mut commands: Commands,
+        campaign_handle: Res<CampaignHandle>,
+        campaigns: Res<Assets<Campaign>>,
+        levels: Res<Assets<Level>>,
+        current_level: Res<CurrentLevel>,
+    ) {
+        let Some(level) = campaigns
+            .get(&campaign_handle.0)
+            .and_then(|campaign| campaign.level(current_level.0))
+            .and_then(|handle| levels.get(handle))
+        else {
+            error!("Level {} is not loaded, no bricks spawned", current_level.0);
+            return;
+        };
+        info!("Spawning {}", level.name);
+        commands.insert_resource(level.grid.clone());
+        commands.insert_resource(level.kinds.clone());
+        let formations: Vec<Entity> = level
+            .formations
+            .iter()
+            .map(|patrol| commands.spawn(Formation::new(patrol.clone())).id())
+            .collect();
+        for brick in &level.bricks {
+            let mut entity = commands.spawn((
+                BrickBundle::new(brick.kind, &level.kinds, brick.position),
+                brick.cell,
+            ));
+            if let Some(formation) = brick.formation {
+                entity.insert((
+                    FormationMember {
+                        formation: formations[formation],
+                        home: brick.position.truncate(),
+                        velocity: Vec2::ZERO,
+                    },
+                    Interpolated::new(brick.position),
+                ));
+            }
+            match brick.role {
+                Some(BrickRole::Explosive { radius }) => {
+                    entity.insert(Explosive { radius });
+                }
+                Some(BrickRole::Indestructible) => {
+                    entity.insert(Indestructible);
+                }
+                Some(BrickRole::Regenerating { delay }) => {
+                    let hit_points = level.kinds.get(brick.kind).hit_points;
+                    entity.insert(Regenerating::new(delay, hit_points));
+                }
+                Some(BrickRole::Hive { interval, spawn }) => {
+                    entity.insert(Hive::new(interval, spawn));
+                }
+                Some(BrickRole::Key(key)) => {
+                    entity.insert(Key { key });
+                }
+                Some(BrickRole::Locked(key)) => {
+                    entity.insert(Locked { key });
+                }
+                None => (),
+            }
+        }
+    }
+
+    // Every ball hit takes a hit point off a brick, a fireball breaks it outright
+    fn damage_brick(
+        mut collision_events: EventReader<CollisionEvent>,
+        mut query: Query<&mut Brick, (Without<Indestructible>, Without<Locked>)>,
+        fireball_query: Query<(), With<Fireball>>,
+    ) {
+        for event in collision_events.read() {
+            if event.kind != CollisionKind::Brick {
+                continue;
+            }
+            let Ok(mut brick) = query.get_mut(event.other) else {
+                continue;
             };
+            // Update brick health
+            if fireball_query.contains(event.ball) {
+                brick.hit_points = 0;
+            } else {
+                brick.damage(1);
+            }
         }
     }
 
-    fn check_win(query: Query<&Brick>, mut next_state: ResMut<NextState<GameState>>) {
-        if query.is_empty() {
+    // Only bricks whose health or role changed need a new colour
+    #[allow(clippy::type_complexity)]
+    fn update_brick_color(
+        mut query: Query<
+            (
+                &Brick,
+                &mut Sprite,
+                Has<Explosive>,
+                Has<Indestructible>,
+                Has<Key>,
+                Has<Locked>,
+                Has<Regenerating>,
+                Has<Hive>,
+            ),
+            Changed<Brick>,
+        >,
+        kinds: Res<BrickKinds>,
+    ) {
+        for (
+            brick,
+            mut sprite,
+            is_explosive,
+            is_steel,
+            is_key,
+            is_locked,
+            is_regenerating,
+            is_hive,
+        ) in &mut query
+        {
+            sprite.color = if is_steel {
+                BRICK_STEEL_COLOR
+            } else if is_explosive {
+                brick.role_color(BRICK_EXPLOSIVE_COLOR, &kinds)
+            } else if is_hive {
+                brick.role_color(BRICK_HIVE_COLOR, &kinds)
+            } else if is_regenerating {
+                let color = brick.color(&kinds);
+                Color::rgb(
+                    (color.r() + REGENERATING_TINT.r()) / 2.0,
+                    (color.g() + REGENERATING_TINT.g()) / 2.0,
+                    (color.b() + REGENERATING_TINT.b()) / 2.0,
+                )
+            } else if is_key {
+                brick.role_color(BRICK_KEY_COLOR, &kinds)
+            } else if is_locked {
+                let color = brick.color(&kinds);
+                Color::rgb(
+                    color.r() * LOCKED_BRIGHTNESS,
+                    color.g() * LOCKED_BRIGHTNESS,
+                    color.b() * LOCKED_BRIGHTNESS,
+                )
+            } else {
+                brick.color(&kinds)
+            };
+        }
+    }
+
+    // Move on to the next level once every breakable brick is gone, or end the run
+    // after the last one. Bricks grown by a hive count from the tick after they appear,
+    // and a hive is itself breakable, so the level cannot be cleared while one is left.
+    pub fn check_win(
+        query: Query<(), (With<Brick>, Without<Indestructible>)>,
+        campaign_handle: Res<CampaignHandle>,
+        campaigns: Res<Assets<Campaign>>,
+        current_level: Res<CurrentLevel>,
+        mut next_state: ResMut<NextState<GameState>>,
+    ) {
+        if !query.is_empty() {
+            return;
+        }
+        let is_last = campaigns
+            .get(&campaign_handle.0)
+            .is_none_or(|campaign| campaign.is_last(current_level.0));
+        if is_last {
             next_state.set(GameState::EndMenu);
+        } else {
+            next_state.set(GameState::LevelCleared);
+        }
+    }
+
+    // Broken explosive bricks damage every brick in reach, which can set off more
+    // explosive bricks. Blasts go off in entity order so a chain plays out the same
+    // way every time.
+    #[allow(clippy::type_complexity)]
+    fn explode_bricks(
+        mut query: Query<(Entity, &mut Brick, &Transform, Option<&Explosive>)>,
+        protected_query: Query<(), Or<(With<Indestructible>, With<Locked>)>>,
+        broadphase: Res<BroadPhase>,
+    ) {
+        let mut broken: Vec<Entity> = query
+            .iter()
+            .filter(|(_, brick, _, explosive)| explosive.is_some() && brick.is_broken())
+            .map(|(entity, ..)| entity)
+            .collect();
+        broken.sort();
+        let mut blasts = VecDeque::from(broken);
+
+        while let Some(entity) = blasts.pop_front() {
+            let Ok((_, _, transform, Some(explosive))) = query.get(entity) else {
+                continue;
+            };
+            let center = transform.translation.truncate();
+            let radius = explosive.radius;
+            let mut caught: Vec<Entity> = broadphase
+                .query(center - Vec2::splat(radius), center + Vec2::splat(radius))
+                .into_iter()
+                .collect();
+            caught.sort();
+            for other in caught {
+                if protected_query.contains(other) {
+                    continue;
+                }
+                let Ok((_, mut brick, transform, explosive)) = query.get_mut(other) else {
+                    continue;
+                };
+                if brick.is_broken() || transform.translation.truncate().distance(center) > radius {
+                    continue;
+                }
+                brick.damage(1);
+                // A brick only breaks once, so each explosive goes off once
+                if brick.is_broken() && explosive.is_some() {
+                    blasts.push_back(other);
+                }
+            }
         }
     }
 
-    // If a brick reach NONE level, remove it
-    fn despawn_brick(
+    // Breaking the last key brick of a key unlocks its locked bricks. Keys broken
+    // earlier are already despawned, so a key opens once none of its bricks is whole.
+    fn unlock_bricks(
         mut commands: Commands,
-        query: Query<(Entity, &Brick)>,
+        key_query: Query<(&Brick, &Key), Without<Locked>>,
+        mut locked_query: Query<(Entity, &mut Brick, &Locked)>,
+    ) {
+        let mut broken_keys = HashSet::new();
+        let mut whole_keys = HashSet::new();
+        for (brick, key) in &key_query {
+            if brick.is_broken() {
+                broken_keys.insert(key.key);
+            } else {
+                whole_keys.insert(key.key);
+            }
+        }
+        for (entity, mut locked_brick, locked) in &mut locked_query {
+            if broken_keys.contains(&locked.key) && !whole_keys.contains(&locked.key) {
+                commands.entity(entity).remove::<Locked>();
+                // Lose the locked look
+                locked_brick.set_changed();
+            }
+        }
+    }
+
+    // Remove bricks that ran out of hit points
+    pub fn despawn_brick(
+        mut commands: Commands,
+        query: Query<(Entity, &Brick, &Transform)>,
         mut update_score_event: EventWriter<UpdateScoreEvent>,
+        mut destroyed_event: EventWriter<BrickDestroyedEvent>,
+        combo: Res<Combo>,
+        kinds: Res<BrickKinds>,
     ) {
-        for (entity, brick) in &query {
-            if let BrickLevel::NONE = brick.level {
+        for (entity, brick, transform) in &query {
+            if brick.is_broken() {
+                let kind = kinds.get(brick.kind);
                 commands.entity(entity).despawn();
-                update_score_event.send(UpdateScoreEvent { score: brick.score });
+                update_score_event.send(UpdateScoreEvent {
+                    score: kind.score,
+                    multiplier: combo.multiplier(),
+                });
+                destroyed_event.send(BrickDestroyedEvent {
+                    hit_points: kind.hit_points,
+                    position: transform.translation,
+                });
+            }
+        }
+    }
+
+    // Regenerating bricks heal a hit point at a time, back up to full health, as long
+    // as nothing damages them for a while
+    fn regenerate_bricks(
+        mut query: Query<(&mut Brick, &mut Regenerating)>,
+        kinds: Res<BrickKinds>,
+        time: Res<Time>,
+    ) {
+        for (mut brick, mut regenerating) in &mut query {
+            if brick.hit_points != regenerating.hit_points {
+                // Any damage starts the wait over
+                regenerating.hit_points = brick.hit_points;
+                regenerating.delay.reset();
+                continue;
+            }
+            if brick.is_broken() || brick.is_full_health(&kinds) {
+                continue;
+            }
+            if regenerating.delay.tick(time.delta()).finished() {
+                brick.heal(&kinds);
+                regenerating.hit_points = brick.hit_points;
+                regenerating.delay.reset();
+            }
+        }
+    }
+
+    // Every so often a hive grows a brick into a free cell next to it. Cells a ball
+    // is passing through are left alone, and a moving hive's bricks join its formation.
+    #[allow(clippy::too_many_arguments)]
+    fn grow_hives(
+        mut commands: Commands,
+        mut hive_query: Query<(
+            &Brick,
+            &GridCell,
+            &Transform,
+            Option<&FormationMember>,
+            &mut Hive,
+        )>,
+        cell_query: Query<&GridCell, With<Brick>>,
+        formation_query: Query<&Formation>,
+        ball_query: Query<(&Transform, &Collider), With<Ball>>,
+        grid: Res<BrickGrid>,
+        kinds: Res<BrickKinds>,
+        time: Res<Time>,
+        mut rng: ResMut<GameRng>,
+    ) {
+        let mut taken: HashSet<GridCell> = cell_query.iter().copied().collect();
+        for (brick, cell, transform, member, mut hive) in &mut hive_query {
+            if !hive.timer.tick(time.delta()).just_finished() || brick.is_broken() {
+                continue;
+            }
+            let formation = member.and_then(|member| {
+                formation_query
+                    .get(member.formation)
+                    .ok()
+                    .map(|formation| (member.formation, formation))
+            });
+            // Where the formation has moved the grid to, and how far it will ever go
+            let shift = member.map_or(Vec2::ZERO, |member| {
+                transform.translation.truncate() - member.home
+            });
+            let (min, max) =
+                formation.map_or((Vec2::ZERO, Vec2::ZERO), |(_, formation)| formation.reach());
+            let collider = Collider::aabb(BRICK_SIZE);
+            let free: Vec<GridCell> = grid
+                .neighbours(*cell)
+                .into_iter()
+                .filter(|neighbour| !taken.contains(neighbour))
+                .filter(|neighbour| {
+                    let home = grid.position(*neighbour);
+                    Level::inside_arena(home.x + min.x, home.y + min.y)
+                        && Level::inside_arena(home.x + max.x, home.y + max.y)
+                })
+                .filter(|neighbour| {
+                    let brick_transform = Transform::from_translation(
+                        (grid.position(*neighbour) + shift).extend(0.0),
+                    );
+                    !ball_query.iter().any(|(ball_transform, ball_collider)| {
+                        ball_collider.bounds_overlap(ball_transform, &collider, &brick_transform)
+                    })
+                })
+                .collect();
+            if free.is_empty() {
+                continue;
             }
+            let neighbour = free[rng.0.usize(..free.len())];
+            let home = grid.position(neighbour);
+            let position = (home + shift).extend(0.0);
+            let mut entity =
+                commands.spawn((BrickBundle::new(hive.spawn, &kinds, position), neighbour));
+            if let Some((formation, _)) = formation {
+                entity.insert((
+                    FormationMember {
+                        formation,
+                        home,
+                        velocity: Vec2::ZERO,
+                    },
+                    Interpolated::new(position),
+                ));
+            }
+            taken.insert(neighbour);
         }
     }
 
-    fn despawn_all_brick(mut commands: Commands, query: Query<Entity, With<Brick>>) {
-        for entity in &query {
+    fn despawn_all_brick(
+        mut commands: Commands,
+        brick_query: Query<Entity, With<Brick>>,
+        formation_query: Query<Entity, With<Formation>>,
+    ) {
+        for entity in brick_query.iter().chain(&formation_query) {
             commands.entity(entity).despawn();
         }
     }
 }
 
-#[derive(Clone)]
-pub enum BrickLevel {
-    NONE, // Will be deleted after update
-    LOW,
-    MID,
-    HIGH,
-    SUPER,
+// Durability, value and look of a kind of brick
+#[derive(Debug, Clone)]
+pub struct BrickKind {
+    // Hits it takes to break
+    pub hit_points: u32,
+    pub score: u32,
+    // Colours from one hit point left up to full health, blended in between
+    pub colors: Vec<Color>,
+}
+
+// Place of a kind in the `BrickKinds` of the level being played
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BrickKindId(usize);
+
+// Kinds of brick the level being played uses, bricks refer to them by id
+#[derive(Resource, Debug, Clone)]
+pub struct BrickKinds {
+    kinds: Vec<BrickKind>,
+    // Id of the kind each cell symbol stands for
+    symbols: HashMap<char, BrickKindId>,
 }
 
-impl BrickLevel {
-    pub fn get_color(&self) -> Color {
-        match self {
-            BrickLevel::NONE => BRICK_LOW_COLOR,
-            BrickLevel::LOW => BRICK_LOW_COLOR,
-            BrickLevel::MID => BRICK_MID_COLOR,
-            BrickLevel::HIGH => BRICK_HIGH_COLOR,
-            BrickLevel::SUPER => BRICK_SUPER_COLOR,
+impl Default for BrickKinds {
+    // Kinds every level can use. Their colours run through `DURABILITY_COLORS`
+    // up to their hit points.
+    fn default() -> Self {
+        let mut kinds = BrickKinds {
+            kinds: Vec::new(),
+            symbols: HashMap::new(),
+        };
+        for &(symbol, hit_points, score) in &DEFAULT_KINDS {
+            kinds.set(
+                symbol,
+                BrickKind {
+                    hit_points,
+                    score,
+                    colors: DURABILITY_COLORS[..hit_points as usize].to_vec(),
+                },
+            );
+        }
+        kinds
+    }
+}
+
+impl BrickKinds {
+    // Give a cell symbol its kind, replacing the one it had under the same id
+    pub fn set(&mut self, symbol: char, kind: BrickKind) -> BrickKindId {
+        if let Some(&id) = self.symbols.get(&symbol) {
+            self.kinds[id.0] = kind;
+            return id;
         }
+        let id = BrickKindId(self.kinds.len());
+        self.kinds.push(kind);
+        self.symbols.insert(symbol, id);
+        id
+    }
+
+    // Kind a cell symbol stands for
+    pub fn id(&self, symbol: char) -> Option<BrickKindId> {
+        self.symbols.get(&symbol).copied()
+    }
+
+    pub fn get(&self, id: BrickKindId) -> &BrickKind {
+        &self.kinds[id.0]
     }
 }
 
+// Brick that never breaks and is not needed to clear the level
+#[derive(Component)]
+pub struct Indestructible;
+
+// Brick that unlocks the bricks locked with the same key once every key brick is broken
+#[derive(Component)]
+pub struct Key {
+    pub key: u8,
+}
+
+// Brick that takes no damage until every key brick of its key is broken
+#[derive(Component)]
+pub struct Locked {
+    pub key: u8,
+}
+
+// Brick that heals one level after going a while without damage
+#[derive(Component)]
+pub struct Regenerating {
+    delay: Timer,
+    // Hit points seen on the last tick, a drop means the brick was damaged
+    hit_points: u32,
+}
+
+impl Regenerating {
+    pub fn new(delay: f32, hit_points: u32) -> Regenerating {
+        Regenerating {
+            delay: Timer::from_seconds(delay, TimerMode::Once),
+            hit_points,
+        }
+    }
+}
+
+// Brick that grows new bricks into the empty cells around it
+#[derive(Component)]
+pub struct Hive {
+    timer: Timer,
+    // Kind of the bricks it grows
+    spawn: BrickKindId,
+}
+
+impl Hive {
+    pub fn new(interval: f32, spawn: BrickKindId) -> Hive {
+        Hive {
+            timer: Timer::from_seconds(interval, TimerMode::Repeating),
+            spawn,
+        }
+    }
+}
+
+// Cell of the level grid a brick was placed in
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct GridCell {
+    pub row: usize,
+    pub col: usize,
+}
+
+// Layout of the cells of the level being played
+#[derive(Resource, Debug, Clone, Default)]
+pub struct BrickGrid {
+    // Centre of the top-left cell
+    pub origin: Vec2,
+    // Distance between cell centres
+    pub spacing: Vec2,
+    pub columns: usize,
+    pub rows: usize,
+}
+
+impl BrickGrid {
+    // Centre of a cell, rows go down from the origin
+    pub fn position(&self, cell: GridCell) -> Vec2 {
+        Vec2::new(
+            self.origin.x + self.spacing.x * cell.col as f32,
+            self.origin.y - self.spacing.y * cell.row as f32,
+        )
+    }
+
+    // Cells above, below, left and right of a cell that are on the grid
+    pub fn neighbours(&self, cell: GridCell) -> Vec<GridCell> {
+        let GridCell { row, col } = cell;
+        [
+            (row.checked_sub(1), Some(col)),
+            (Some(row + 1), Some(col)),
+            (Some(row), col.checked_sub(1)),
+            (Some(row), Some(col + 1)),
+        ]
+        .into_iter()
+        .filter_map(|(row, col)| {
+            Some(GridCell {
+                row: row?,
+                col: col?,
+            })
+        })
+        .filter(|cell| cell.row < self.rows && cell.col < self.columns)
+        .collect()
+    }
+}
+
+// Brick that damages its surroundings when broken
+#[derive(Component)]
+pub struct Explosive {
+    pub radius: f32,
+}
+
 #[derive(Component)]
 pub struct Brick {
-    pub level: BrickLevel,
-    pub score: u32,
+    pub hit_points: u32,
+    // Kind the brick was spawned as, its `hit_points` are the brick's full health
+    pub kind: BrickKindId,
+}
+
+impl Brick {
+    pub fn new(kind: BrickKindId, kinds: &BrickKinds) -> Brick {
+        Brick {
+            hit_points: kinds.get(kind).hit_points,
+            kind,
+        }
+    }
+
+    // Broken bricks are only waiting to be despawned
+    pub fn is_broken(&self) -> bool {
+        self.hit_points == 0
+    }
+
+    pub fn damage(&mut self, hits: u32) {
+        self.hit_points = self.hit_points.saturating_sub(hits);
+    }
+
+    pub fn is_full_health(&self, kinds: &BrickKinds) -> bool {
+        self.hit_points >= kinds.get(self.kind).hit_points
+    }
+
+    // One hit point back, never past full health
+    pub fn heal(&mut self, kinds: &BrickKinds) {
+        if !self.is_broken() {
+            self.hit_points = (self.hit_points + 1).min(kinds.get(self.kind).hit_points);
+        }
+    }
+
+    // From 0 with one hit point left up to 1 at full health
+    fn health(&self, kinds: &BrickKinds) -> f32 {
+        let full = kinds.get(self.kind).hit_points;
+        if full <= 1 {
+            return 1.0;
+        }
+        ((self.hit_points.max(1) - 1) as f32 / (full - 1) as f32).min(1.0)
+    }
+
+    // Colour of the kind's gradient for the hit points left
+    pub fn color(&self, kinds: &BrickKinds) -> Color {
+        let colors = &kinds.get(self.kind).colors;
+        let Some(&full) = colors.last() else {
+            return Color::WHITE;
+        };
+        if colors.len() == 1 {
+            return full;
+        }
+        let stop = self.health(kinds) * (colors.len() - 1) as f32;
+        let index = (stop as usize).min(colors.len() - 2);
+        let (from, to) = (colors[index], colors[index + 1]);
+        let blend = stop - index as f32;
+        Color::rgb(
+            from.r() + (to.r() - from.r()) * blend,
+            from.g() + (to.g() - from.g()) * blend,
+            from.b() + (to.b() - from.b()) * blend,
+        )
+    }
+
+    // Colour of a special brick's role, darker for every hit point lost
+    pub fn role_color(&self, color: Color, kinds: &BrickKinds) -> Color {
+        let brightness = DAMAGED_BRIGHTNESS + (1.0 - DAMAGED_BRIGHTNESS) * self.health(kinds);
+        Color::rgb(
+            color.r() * brightness,
+            color.g() * brightness,
+            color.b() * brightness,
+        )
+    }
 }
 
 #[derive(Bundle)]
@@ -143,24 +732,13 @@ pub struct BrickBundle {
 }
 
 impl BrickBundle {
-    pub fn new(hp: BrickLevel, location: Vec3) -> BrickBundle {
-        let color = hp.get_color();
+    pub fn new(kind: BrickKindId, kinds: &BrickKinds, location: Vec3) -> BrickBundle {
+        let brick = Brick::new(kind, kinds);
+        let color = brick.color(kinds);
         BrickBundle {
-            brick: Brick {
-                level: hp.clone(),
-                score: match hp {
-                    BrickLevel::LOW => 1,
-                    BrickLevel::MID => 2,
-                    BrickLevel::HIGH => 3,
-                    BrickLevel::SUPER => 5,
-                    BrickLevel::NONE => 0,
-                },
-            },
+            brick,
             sprite: SpriteBundle {
-                sprite: Sprite {
-                    color: color,
-                    ..default()
-                },
+                sprite: Sprite { color, ..default() },
                 transform: Transform {
                     translation: location,
                     scale: BRICK_SIZE,
@@ -168,7 +746,136 @@ impl BrickBundle {
                 },
                 ..Default::default()
             },
-            collider: Collider,
+            collider: Collider::aabb(BRICK_SIZE),
         }
     }
+
+    pub fn with_size(mut self, size: Vec3) -> BrickBundle {
+        self.sprite.transform.scale = size;
+        self.collider = Collider::aabb(size);
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::RunSystemOnce;
+
+    use super::*;
+    use crate::broadphase::BroadPhasePlugin;
+
+    // Single hit point brick in the row at `x`
+    fn spawn_brick(world: &mut World, x: f32) -> EntityWorldMut<'_> {
+        let kinds = BrickKinds::default();
+        world.spawn((
+            Brick::new(kinds.id(LOW_KIND).unwrap(), &kinds),
+            Transform::from_xyz(x, 0.0, 0.0),
+            Collider::aabb(Vec3::new(40.0, 20.0, 0.0)),
+        ))
+    }
+
+    #[test]
+    fn blast_sets_off_a_chain() {
+        let mut world = World::new();
+        world.init_resource::<BroadPhase>();
+        let first = spawn_brick(&mut world, 0.0)
+            .insert(Explosive { radius: 60.0 })
+            .id();
+        let second = spawn_brick(&mut world, 50.0)
+            .insert(Explosive { radius: 60.0 })
+            .id();
+        // Only in reach of the second blast
+        let chained = spawn_brick(&mut world, 100.0).id();
+        let steel = spawn_brick(&mut world, -50.0).insert(Indestructible).id();
+        let out_of_reach = spawn_brick(&mut world, -120.0).id();
+        world.run_system_once(BroadPhasePlugin::sync_broadphase);
+
+        world.get_mut::<Brick>(first).unwrap().damage(1);
+        world.run_system_once(BrickPlugin::explode_bricks);
+
+        let broken = |world: &World, entity| world.get::<Brick>(entity).unwrap().is_broken();
+        assert!(broken(&world, second));
+        assert!(broken(&world, chained));
+        assert!(!broken(&world, steel));
+        assert!(!broken(&world, out_of_reach));
+    }
+
+    #[test]
+    fn locked_bricks_open_after_every_key() {
+        let mut world = World::new();
+        let first_key = spawn_brick(&mut world, 0.0).insert(Key { key: 1 }).id();
+        let second_key = spawn_brick(&mut world, 50.0).insert(Key { key: 1 }).id();
+        spawn_brick(&mut world, 100.0).insert(Key { key: 2 });
+        let locked = spawn_brick(&mut world, 150.0)
+            .insert(Locked { key: 1 })
+            .id();
+        let other_lock = spawn_brick(&mut world, 200.0)
+            .insert(Locked { key: 2 })
+            .id();
+
+        world.get_mut::<Brick>(first_key).unwrap().damage(1);
+        world.run_system_once(BrickPlugin::unlock_bricks);
+        assert!(world.get::<Locked>(locked).is_some());
+
+        // Broken bricks are gone by the time the next key breaks
+        world.despawn(first_key);
+        world.get_mut::<Brick>(second_key).unwrap().damage(1);
+        world.run_system_once(BrickPlugin::unlock_bricks);
+        assert!(world.get::<Locked>(locked).is_none());
+        assert!(world.get::<Locked>(other_lock).is_some());
+    }
+
+    // Only level of the campaign, so a win ends the run
+    fn win_world() -> World {
+        let mut world = World::new();
+        world.init_resource::<Assets<Campaign>>();
+        world.insert_resource(CampaignHandle(Handle::default()));
+        world.insert_resource(CurrentLevel(0));
+        world.init_resource::<NextState<GameState>>();
+        world
+    }
+
+    fn check_win(world: &mut World) -> Option<GameState> {
+        world.run_system_once(BrickPlugin::check_win);
+        world.resource::<NextState<GameState>>().0.clone()
+    }
+
+    #[test]
+    fn win_ignores_indestructible_bricks() {
+        let mut world = win_world();
+        spawn_brick(&mut world, 0.0).insert(Indestructible);
+        spawn_brick(&mut world, 50.0).insert(Indestructible);
+        assert_eq!(check_win(&mut world), Some(GameState::EndMenu));
+    }
+
+    #[test]
+    fn locked_bricks_stop_a_win() {
+        let mut world = win_world();
+        spawn_brick(&mut world, 0.0).insert(Indestructible);
+        spawn_brick(&mut world, 50.0).insert(Locked { key: 1 });
+        assert_eq!(check_win(&mut world), None);
+    }
+
+    #[test]
+    fn role_color_darkens_with_damage() {
+        let kinds = BrickKinds::default();
+        let mut brick = Brick::new(kinds.id(HIGH_KIND).unwrap(), &kinds);
+        assert_eq!(brick.role_color(BRICK_KEY_COLOR, &kinds), BRICK_KEY_COLOR);
+        brick.damage(1);
+        let damaged = brick.role_color(BRICK_KEY_COLOR, &kinds);
+        brick.damage(1);
+        let last_hit_point = brick.role_color(BRICK_KEY_COLOR, &kinds);
+        assert!(damaged.r() < BRICK_KEY_COLOR.r() && last_hit_point.r() < damaged.r());
+        assert!((last_hit_point.g() - BRICK_KEY_COLOR.g() * DAMAGED_BRIGHTNESS).abs() < 1e-6);
+    }
+
+    #[test]
+    fn single_hit_point_role_color_is_full() {
+        let kinds = BrickKinds::default();
+        let brick = Brick::new(kinds.id(LOW_KIND).unwrap(), &kinds);
+        assert_eq!(
+            brick.role_color(BRICK_EXPLOSIVE_COLOR, &kinds),
+            BRICK_EXPLOSIVE_COLOR
+        );
+    }
 }
@@ -0,0 +1,217 @@
+use bevy::prelude::*;
+use bevy::utils::{HashMap, HashSet};
+
+use crate::ball::Ball;
+use crate::collider::{Collider, ColliderPlugin};
+use crate::laser::LaserBolt;
+use crate::powerup::PowerUp;
+use crate::schedule::InGameSet;
+
+// Side of a grid cell, close to the size of a brick
+const CELL_SIZE: f32 = 64.0;
+
+// Uniform grid of the static and slow colliders, balls are looked up against it
+#[derive(Resource, Default)]
+pub struct BroadPhase {
+    cells: HashMap<IVec2, Vec<Entity>>,
+    // Cell range each entity is stored in
+    entries: HashMap<Entity, (IVec2, IVec2)>,
+}
+
+impl BroadPhase {
+    fn cell_range(min: Vec2, max: Vec2) -> (IVec2, IVec2) {
+        (
+            (min / CELL_SIZE).floor().as_ivec2(),
+            (max / CELL_SIZE).floor().as_ivec2(),
+        )
+    }
+
+    fn insert(&mut self, entity: Entity, transform: &Transform, collider: &Collider) {
+        let (min, max) = collider.bounds(transform);
+        let (min_cell, max_cell) = BroadPhase::cell_range(min, max);
+        // Moving within the same cells leaves the grid as it is
+        if self.entries.get(&entity) == Some(&(min_cell, max_cell)) {
+            return;
+        }
+        self.remove(entity);
+        for x in min_cell.x..=max_cell.x {
+            for y in min_cell.y..=max_cell.y {
+                self.cells.entry(IVec2::new(x, y)).or_default().push(entity);
+            }
+        }
+        self.entries.insert(entity, (min_cell, max_cell));
+    }
+
+    fn remove(&mut self, entity: Entity) {
+        let Some((min_cell, max_cell)) = self.entries.remove(&entity) else {
+            return;
+        };
+        for x in min_cell.x..=max_cell.x {
+            for y in min_cell.y..=max_cell.y {
+                let cell = IVec2::new(x, y);
+                if let Some(entities) = self.cells.get_mut(&cell) {
+                    entities.retain(|other| *other != entity);
+                    if entities.is_empty() {
+                        self.cells.remove(&cell);
+                    }
+                }
+            }
+        }
+    }
+
+    // Every entity sharing a cell with the given area, each listed once
+    pub fn query(&self, min: Vec2, max: Vec2) -> HashSet<Entity> {
+        let (min_cell, max_cell) = BroadPhase::cell_range(min, max);
+        let mut found = HashSet::new();
+        for x in min_cell.x..=max_cell.x {
+            for y in min_cell.y..=max_cell.y {
+                if let Some(entities) = self.cells.get(&IVec2::new(x, y)) {
+                    found.extend(entities.iter().copied());
+                }
+            }
+        }
+        found
+    }
+}
+
+pub struct BroadPhasePlugin;
+
+impl Plugin for BroadPhasePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<BroadPhase>()
+            .add_systems(
+                FixedUpdate,
+                BroadPhasePlugin::sync_broadphase
+                    .in_set(InGameSet::CollisionDetection)
+                    .before(ColliderPlugin::push_balls_out),
+            )
+            // Despawns happen in many schedules, catch them all once per frame
+            .add_systems(Last, BroadPhasePlugin::prune_broadphase);
+    }
+}
+
+impl BroadPhasePlugin {
+    // Interpolated entities change `Transform` every frame, only those leaving
+    // their cells are stored again
+    #[allow(clippy::type_complexity)]
+    pub fn sync_broadphase(
+        mut broadphase: ResMut<BroadPhase>,
+        query: Query<
+            (Entity, &Transform, &Collider),
+            (
+                Without<Ball>,
+                Without<PowerUp>,
+                Without<LaserBolt>,
+                Or<(Changed<Collider>, Changed<Transform>)>,
+            ),
+        >,
+    ) {
+        for (entity, transform, collider) in &query {
+            broadphase.insert(entity, transform, collider);
+        }
+    }
+
+    fn prune_broadphase(
+        mut broadphase: ResMut<BroadPhase>,
+        mut removed: RemovedComponents<Collider>,
+    ) {
+        for entity in removed.read() {
+            broadphase.remove(entity);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::RunSystemOnce;
+
+    use super::*;
+
+    // Brick sized box, a little smaller than a cell
+    fn brick() -> Collider {
+        Collider::Aabb {
+            half_extents: Vec2::new(25.0, 10.0),
+        }
+    }
+
+    fn at(x: f32, y: f32) -> Transform {
+        Transform::from_xyz(x, y, 0.0)
+    }
+
+    fn entity(index: u32) -> Entity {
+        Entity::from_raw(index)
+    }
+
+    #[test]
+    fn inserted_entity_is_found_around_it() {
+        let mut broadphase = BroadPhase::default();
+        broadphase.insert(entity(0), &at(32.0, 32.0), &brick());
+        let found = broadphase.query(Vec2::new(0.0, 0.0), Vec2::new(10.0, 10.0));
+        assert!(found.contains(&entity(0)));
+        assert!(broadphase
+            .query(Vec2::new(200.0, 200.0), Vec2::new(210.0, 210.0))
+            .is_empty());
+    }
+
+    #[test]
+    fn entity_across_cells_is_listed_once() {
+        let mut broadphase = BroadPhase::default();
+        // Straddles the corner shared by four cells
+        broadphase.insert(entity(0), &at(0.0, 0.0), &brick());
+        for (x, y) in [(-10.0, -10.0), (10.0, -10.0), (-10.0, 10.0), (10.0, 10.0)] {
+            let found = broadphase.query(Vec2::new(x, y), Vec2::new(x, y));
+            assert!(found.contains(&entity(0)), "missing from ({}, {})", x, y);
+        }
+        let found = broadphase.query(Vec2::splat(-100.0), Vec2::splat(100.0));
+        assert_eq!(found.len(), 1);
+    }
+
+    #[test]
+    fn query_across_cells_finds_each_entity() {
+        let mut broadphase = BroadPhase::default();
+        broadphase.insert(entity(0), &at(32.0, 32.0), &brick());
+        broadphase.insert(entity(1), &at(160.0, 32.0), &brick());
+        broadphase.insert(entity(2), &at(32.0, 160.0), &brick());
+        let found = broadphase.query(Vec2::new(10.0, 10.0), Vec2::new(150.0, 40.0));
+        assert!(found.contains(&entity(0)) && found.contains(&entity(1)));
+        assert!(!found.contains(&entity(2)));
+    }
+
+    #[test]
+    fn moved_entity_leaves_its_old_cells() {
+        let mut broadphase = BroadPhase::default();
+        broadphase.insert(entity(0), &at(32.0, 32.0), &brick());
+        broadphase.insert(entity(0), &at(288.0, 32.0), &brick());
+        assert!(broadphase
+            .query(Vec2::new(30.0, 30.0), Vec2::new(34.0, 34.0))
+            .is_empty());
+        let found = broadphase.query(Vec2::new(286.0, 30.0), Vec2::new(290.0, 34.0));
+        assert!(found.contains(&entity(0)));
+        assert_eq!(broadphase.entries.len(), 1);
+    }
+
+    #[test]
+    fn move_within_cells_keeps_the_entry() {
+        let mut broadphase = BroadPhase::default();
+        broadphase.insert(entity(0), &at(32.0, 32.0), &brick());
+        broadphase.insert(entity(0), &at(34.0, 31.0), &brick());
+        assert_eq!(broadphase.cells[&IVec2::ZERO], [entity(0)]);
+    }
+
+    #[test]
+    fn removed_collider_is_pruned() {
+        let mut world = World::new();
+        world.init_resource::<BroadPhase>();
+        let kept = world.spawn((at(32.0, 32.0), brick())).id();
+        let despawned = world.spawn((at(40.0, 40.0), brick())).id();
+        world.run_system_once(BroadPhasePlugin::sync_broadphase);
+        world.despawn(despawned);
+        world.run_system_once(BroadPhasePlugin::prune_broadphase);
+
+        let broadphase = world.resource::<BroadPhase>();
+        let found = broadphase.query(Vec2::splat(30.0), Vec2::splat(42.0));
+        assert!(found.contains(&kept));
+        assert!(!found.contains(&despawned));
+        assert!(!broadphase.entries.contains_key(&despawned));
+    }
+}
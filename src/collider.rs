@@ -1,165 +1,774 @@
+use bevy::ecs::system::SystemParam;
 use bevy::prelude::*;
 
-use crate::ball::{Ball, BALL_DIAMETER};
-use crate::brick::{Brick, BrickLevel};
+use crate::ball::{AttachedToPaddle, Ball, Fireball};
+use crate::brick::{Brick, Indestructible, Locked};
+use crate::broadphase::BroadPhase;
+use crate::events::{CollisionEvent, CollisionKind};
+use crate::formation::FormationMember;
 use crate::paddle::Paddle;
 use crate::schedule::InGameSet;
 use crate::wall::Wall;
 
-#[derive(Component)]
-pub struct Collider;
+// Surfaces a ball can bounce off within a single tick
+const MAX_CONTACTS_PER_TICK: usize = 4;
+
+// Physical shape of an entity, centred on its translation and independent of its scale
+#[derive(Component, Clone, Copy, Debug)]
+pub enum Collider {
+    Aabb {
+        half_extents: Vec2,
+    },
+    Circle {
+        radius: f32,
+    },
+    // Segment along the local x axis, rounded by the radius
+    Capsule {
+        half_length: f32,
+        radius: f32,
+    },
+    // Box turned by `rotation` radians on top of the entity's rotation
+    #[allow(dead_code)]
+    Obb {
+        half_extents: Vec2,
+        rotation: f32,
+    },
+}
+
+impl Collider {
+    pub fn aabb(size: Vec3) -> Collider {
+        Collider::Aabb {
+            half_extents: size.truncate() / 2.0,
+        }
+    }
+
+    // World space bounding box
+    pub fn bounds(&self, transform: &Transform) -> (Vec2, Vec2) {
+        let center = transform.translation.truncate();
+        let half = match *self {
+            Collider::Aabb { half_extents } => half_extents,
+            Collider::Circle { radius } => Vec2::splat(radius),
+            Collider::Capsule {
+                half_length,
+                radius,
+            } => {
+                let axis = (transform.rotation * Vec3::X).truncate() * half_length;
+                axis.abs() + Vec2::splat(radius)
+            }
+            Collider::Obb {
+                half_extents,
+                rotation,
+            } => {
+                let rotation = transform.rotation * Quat::from_rotation_z(rotation);
+                let x_axis = (rotation * Vec3::X).truncate() * half_extents.x;
+                let y_axis = (rotation * Vec3::Y).truncate() * half_extents.y;
+                x_axis.abs() + y_axis.abs()
+            }
+        };
+        (center - half, center + half)
+    }
+
+    // Whether the bounding boxes of two colliders touch
+    pub fn bounds_overlap(
+        &self,
+        transform: &Transform,
+        other: &Collider,
+        other_transform: &Transform,
+    ) -> bool {
+        let (min, max) = self.bounds(transform);
+        let (other_min, other_max) = other.bounds(other_transform);
+        min.cmple(other_max).all() && other_min.cmple(max).all()
+    }
+
+    // Whether two colliders touch. A capsule against a box sweeps the circle at one
+    // end of the capsule to the other, other pairs only compare their bounding boxes.
+    pub fn overlaps(
+        &self,
+        transform: &Transform,
+        other: &Collider,
+        other_transform: &Transform,
+    ) -> bool {
+        match (*self, *other) {
+            (
+                Collider::Capsule {
+                    half_length,
+                    radius,
+                },
+                Collider::Aabb { half_extents },
+            ) => {
+                let axis = (transform.rotation * Vec3::X).truncate() * half_length;
+                let start = transform.translation.truncate() - axis;
+                let center = other_transform.translation.truncate();
+                let closest = start.clamp(center - half_extents, center + half_extents);
+                (start - closest).length_squared() <= radius * radius
+                    || ColliderPlugin::sweep_circle_box(
+                        start,
+                        axis * 2.0,
+                        radius,
+                        center,
+                        half_extents,
+                    )
+                    .is_some()
+            }
+            (Collider::Aabb { .. }, Collider::Capsule { .. }) => {
+                other.overlaps(other_transform, self, transform)
+            }
+            _ => self.bounds_overlap(transform, other, other_transform),
+        }
+    }
+}
+
 pub struct ColliderPlugin;
 
 impl Plugin for ColliderPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(
-            Update,
-            ColliderPlugin::check_for_collision.in_set(InGameSet::CollisionDetection),
+            FixedUpdate,
+            (
+                ColliderPlugin::push_balls_out,
+                ColliderPlugin::move_and_collide,
+                ColliderPlugin::collide_balls,
+            )
+                .chain()
+                .in_set(InGameSet::CollisionDetection),
         );
     }
 }
 
-impl ColliderPlugin {
-    // Return normal vector
-    fn collision_with_square(
-        ball_transform: &Transform,
-        wall_transform: &Transform,
-    ) -> Option<Vec2> {
-        let wall_top = wall_transform.translation.y + wall_transform.scale.y / 2.0;
-        let wall_bottom = wall_transform.translation.y - wall_transform.scale.y / 2.0;
-        let wall_left = wall_transform.translation.x - wall_transform.scale.x / 2.0;
-        let wall_right = wall_transform.translation.x + wall_transform.scale.x / 2.0;
-        let ball_top = ball_transform.translation.y + BALL_DIAMETER/ 2.0;
-        let ball_bottom = ball_transform.translation.y - BALL_DIAMETER / 2.0;
-        let ball_left = ball_transform.translation.x - BALL_DIAMETER / 2.0;
-        let ball_right = ball_transform.translation.x + BALL_DIAMETER / 2.0;
-        if ball_right < wall_left
-            || ball_left > wall_right
-            || ball_top < wall_bottom
-            || ball_bottom > wall_top
-        {
-            return None;
-        } else if ball_transform.translation.y > wall_top {
-            // In the top strip
-            if ball_transform.translation.x < wall_left {
-                let dist_lt_vec =
-                    Vec2::new(ball_transform.translation.x, ball_transform.translation.y)
-                        - Vec2::new(wall_left, wall_top);
-                if dist_lt_vec.length() < ball_transform.scale.x / 2.0 {
-                    return Some(dist_lt_vec.normalize());
-                } else {
-                    return None;
-                }
-            } else if ball_transform.translation.x > wall_right {
-                let dist_rt_vec =
-                    Vec2::new(ball_transform.translation.x, ball_transform.translation.y)
-                        - Vec2::new(wall_right, wall_top);
-                if dist_rt_vec.length() < ball_transform.scale.x / 2.0 {
-                    return Some(dist_rt_vec.normalize());
-                } else {
-                    return None;
+// First contact along a ball's motion
+struct Hit {
+    // Fraction of the motion travelled before contact
+    time: f32,
+    normal: Vec2,
+    entity: Entity,
+    kind: CollisionKind,
+    // Velocity of the surface that was hit
+    velocity: Vec2,
+}
+
+impl Hit {
+    fn earliest(current: Option<Hit>, candidate: Hit) -> Option<Hit> {
+        match current {
+            Some(hit) if hit.time <= candidate.time => Some(hit),
+            _ => Some(candidate),
+        }
+    }
+}
+
+// Everything a ball can run into, found through the broadphase
+#[derive(SystemParam)]
+#[allow(clippy::type_complexity)]
+pub struct Surfaces<'w, 's> {
+    brick_query: Query<
+        'w,
+        's,
+        (
+            &'static Brick,
+            &'static Transform,
+            &'static Collider,
+            Has<Indestructible>,
+            Has<Locked>,
+            Option<&'static FormationMember>,
+        ),
+        Without<Ball>,
+    >,
+    paddle_query:
+        Query<'w, 's, (&'static Transform, &'static Collider), (With<Paddle>, Without<Ball>)>,
+    wall_query: Query<'w, 's, (&'static Transform, &'static Collider), (With<Wall>, Without<Ball>)>,
+    broadphase: Res<'w, BroadPhase>,
+}
+
+impl Surfaces<'_, '_> {
+    // First surface a circle moving by `motion` over `duration` seconds runs into
+    fn first_hit(
+        &self,
+        start: Vec2,
+        motion: Vec2,
+        radius: f32,
+        duration: f32,
+        ignored: &[Entity],
+    ) -> Option<Hit> {
+        // Only colliders near the swept path need the exact test
+        let end = start + motion;
+        let candidates = self.broadphase.query(
+            start.min(end) - Vec2::splat(radius),
+            start.max(end) + Vec2::splat(radius),
+        );
+        let mut first_hit = None;
+        for entity in candidates {
+            if ignored.contains(&entity) {
+                continue;
+            }
+            let (kind, transform, collider, velocity) = if let Ok((
+                brick,
+                transform,
+                collider,
+                _,
+                _,
+                member,
+            )) = self.brick_query.get(entity)
+            {
+                // Broken bricks are only waiting to be despawned
+                if brick.is_broken() {
+                    continue;
                 }
+                let velocity = member.map_or(Vec2::ZERO, |member| member.velocity);
+                (CollisionKind::Brick, transform, collider, velocity)
+            } else if let Ok((transform, collider)) = self.paddle_query.get(entity) {
+                (CollisionKind::Paddle, transform, collider, Vec2::ZERO)
+            } else if let Ok((transform, collider)) = self.wall_query.get(entity) {
+                (CollisionKind::Wall, transform, collider, Vec2::ZERO)
             } else {
-                return Some(Vec2::new(0.0, 1.0));
+                continue;
+            };
+            // Sweep in the frame of a moving collider, starting from where it was
+            // when this part of the motion began, so it can also run into the ball
+            let surface_motion = velocity * duration;
+            let mut swept_transform = *transform;
+            swept_transform.translation -= surface_motion.extend(0.0);
+            if let Some((time, normal)) = ColliderPlugin::sweep_circle(
+                start,
+                motion - surface_motion,
+                radius,
+                collider,
+                &swept_transform,
+            ) {
+                let hit = Hit {
+                    time,
+                    normal,
+                    entity,
+                    kind,
+                    velocity,
+                };
+                first_hit = Hit::earliest(first_hit, hit);
             }
-        } else if ball_transform.translation.y > wall_bottom {
-            if ball_transform.translation.x < wall_left {
-                return Some(Vec2::new(-1.0, 0.0));
-            } else if ball_transform.translation.x > wall_right {
-                return Some(Vec2::new(1.0, 0.0));
-            } else {
-                return None;
+        }
+        first_hit
+    }
+
+    // Part of `motion` a circle can travel before it would run into a surface
+    pub fn clamp_motion(&self, start: Vec2, motion: Vec2, radius: f32) -> Vec2 {
+        self.first_hit(start, motion, radius, 0.0, &[])
+            .map_or(motion, |hit| motion * hit.time)
+    }
+
+    // Move taking a circle out of the bricks that jumped onto it
+    fn push_out(&self, center: Vec2, radius: f32) -> Vec2 {
+        let candidates = self
+            .broadphase
+            .query(center - Vec2::splat(radius), center + Vec2::splat(radius));
+        let mut push = Vec2::ZERO;
+        for entity in candidates {
+            let Ok((brick, transform, collider, _, _, _)) = self.brick_query.get(entity) else {
+                continue;
+            };
+            let Collider::Aabb { half_extents } = *collider else {
+                continue;
+            };
+            if brick.is_broken() {
+                continue;
+            }
+            if let Some(out) = ColliderPlugin::push_circle_out_of_box(
+                center + push,
+                radius,
+                transform.translation.truncate(),
+                half_extents,
+            ) {
+                push += out;
+            }
+        }
+        push
+    }
+
+    // Only bricks that can be broken are pierced by fireballs
+    fn is_pierceable(&self, entity: Entity) -> bool {
+        matches!(self.brick_query.get(entity), Ok((_, _, _, false, false, _)))
+    }
+}
+
+impl ColliderPlugin {
+    // Time of impact in [0, 1] and surface normal of a circle moving by `motion` into a shape
+    fn sweep_circle(
+        start: Vec2,
+        motion: Vec2,
+        radius: f32,
+        collider: &Collider,
+        transform: &Transform,
+    ) -> Option<(f32, Vec2)> {
+        let center = transform.translation.truncate();
+        match *collider {
+            Collider::Aabb { half_extents } => {
+                ColliderPlugin::sweep_circle_box(start, motion, radius, center, half_extents)
             }
-        } else {
-            // In the bottom strip
-            if ball_transform.translation.x < wall_left {
-                let dist_lb_vec =
-                    Vec2::new(ball_transform.translation.x, ball_transform.translation.y)
-                        - Vec2::new(wall_left, wall_bottom);
-                if dist_lb_vec.length() < ball_transform.scale.x / 2.0 {
-                    return Some(dist_lb_vec.normalize());
+            Collider::Circle {
+                radius: other_radius,
+            } => ColliderPlugin::sweep_circle_point(start, motion, radius + other_radius, center),
+            // A capsule is a box with no height, grown by both radii
+            Collider::Capsule {
+                half_length,
+                radius: other_radius,
+            } => ColliderPlugin::sweep_circle_rotated_box(
+                start,
+                motion,
+                radius + other_radius,
+                Vec2::new(half_length, 0.0),
+                transform,
+            ),
+            Collider::Obb {
+                half_extents,
+                rotation,
+            } => ColliderPlugin::sweep_circle_rotated_box(
+                start,
+                motion,
+                radius,
+                half_extents,
+                &transform.with_rotation(transform.rotation * Quat::from_rotation_z(rotation)),
+            ),
+        }
+    }
+
+    // Sweep in the box's own frame and turn the normal back to world space
+    fn sweep_circle_rotated_box(
+        start: Vec2,
+        motion: Vec2,
+        radius: f32,
+        half_extents: Vec2,
+        transform: &Transform,
+    ) -> Option<(f32, Vec2)> {
+        let to_local = transform.rotation.inverse();
+        let local_start =
+            (to_local * (start - transform.translation.truncate()).extend(0.0)).truncate();
+        let local_motion = (to_local * motion.extend(0.0)).truncate();
+        let (time, normal) = ColliderPlugin::sweep_circle_box(
+            local_start,
+            local_motion,
+            radius,
+            Vec2::ZERO,
+            half_extents,
+        )?;
+        Some((time, (transform.rotation * normal.extend(0.0)).truncate()))
+    }
+
+    // Shortest move taking a circle out of a box it overlaps
+    fn push_circle_out_of_box(
+        center: Vec2,
+        radius: f32,
+        box_center: Vec2,
+        half: Vec2,
+    ) -> Option<Vec2> {
+        let closest = center.clamp(box_center - half, box_center + half);
+        let offset = center - closest;
+        if offset == Vec2::ZERO {
+            // Centre inside the box, out through the nearest face
+            let relative = center - box_center;
+            let depth = half - relative.abs() + Vec2::splat(radius);
+            return Some(if depth.x < depth.y {
+                Vec2::new(depth.x * relative.x.signum(), 0.0)
+            } else {
+                Vec2::new(0.0, depth.y * relative.y.signum())
+            });
+        }
+        let distance = offset.length();
+        (distance < radius).then(|| offset / distance * (radius - distance))
+    }
+
+    // Time of impact in [0, 1] and surface normal of a circle moving by `motion` into a box
+    fn sweep_circle_box(
+        start: Vec2,
+        motion: Vec2,
+        radius: f32,
+        center: Vec2,
+        half: Vec2,
+    ) -> Option<(f32, Vec2)> {
+        // Already touching, only a hit if the ball is heading into the box
+        let closest = start.clamp(center - half, center + half);
+        let offset = start - closest;
+        if offset.length_squared() <= radius * radius {
+            let normal = if offset == Vec2::ZERO {
+                // Centre inside the box, push out along the shallowest axis
+                let depth = half - (start - center).abs();
+                if depth.x < depth.y {
+                    Vec2::new((start.x - center.x).signum(), 0.0)
                 } else {
-                    return None;
+                    Vec2::new(0.0, (start.y - center.y).signum())
                 }
-            } else if ball_transform.translation.x > wall_right {
-                let dist_rb_vec =
-                    Vec2::new(ball_transform.translation.x, ball_transform.translation.y)
-                        - Vec2::new(wall_right, wall_bottom);
-                if dist_rb_vec.length() < ball_transform.scale.x / 2.0 {
-                    return Some(dist_rb_vec.normalize());
-                } else {
+            } else {
+                offset.normalize()
+            };
+            return (motion.dot(normal) < 0.0).then_some((0.0, normal));
+        }
+
+        // Slab test against the box grown by the radius
+        let grown = half + Vec2::splat(radius);
+        let mut t_enter = f32::NEG_INFINITY;
+        let mut t_exit = f32::INFINITY;
+        let mut normal = Vec2::ZERO;
+        for axis in 0..2 {
+            if motion[axis] == 0.0 {
+                if (start[axis] - center[axis]).abs() > grown[axis] {
                     return None;
                 }
-            } else {
-                return Some(Vec2::new(0.0, -1.0));
+                continue;
+            }
+            let t0 = (center[axis] - grown[axis] - start[axis]) / motion[axis];
+            let t1 = (center[axis] + grown[axis] - start[axis]) / motion[axis];
+            let (near, far) = if t0 < t1 { (t0, t1) } else { (t1, t0) };
+            if near > t_enter {
+                t_enter = near;
+                normal = Vec2::ZERO;
+                normal[axis] = -motion[axis].signum();
             }
+            t_exit = t_exit.min(far);
+        }
+        if t_enter > t_exit || t_exit < 0.0 || t_enter > 1.0 {
+            return None;
+        }
+
+        // Entering beyond both edges of the box means the rounded corner is hit
+        let point = start + motion * t_enter.max(0.0);
+        let outside = (point - center).abs() - half;
+        if outside.x > 0.0 && outside.y > 0.0 {
+            let corner = center + half * (point - center).signum();
+            return ColliderPlugin::sweep_circle_point(start, motion, radius, corner);
+        }
+        Some((t_enter, normal))
+    }
+
+    // Time of impact in [0, 1] and normal of a circle moving by `motion` into a point
+    fn sweep_circle_point(
+        start: Vec2,
+        motion: Vec2,
+        radius: f32,
+        point: Vec2,
+    ) -> Option<(f32, Vec2)> {
+        let offset = start - point;
+        let a = motion.dot(motion);
+        let b = offset.dot(motion);
+        let c = offset.dot(offset) - radius * radius;
+        let discriminant = b * b - a * c;
+        if a == 0.0 || discriminant < 0.0 {
+            return None;
+        }
+        let time = (-b - discriminant.sqrt()) / a;
+        if !(0.0..=1.0).contains(&time) {
+            return None;
         }
+        Some((time, (offset + motion * time).normalize()))
     }
 
-    pub fn check_for_collision(
-        mut ball_query: Query<(&mut Ball, &Transform)>,
-        mut brick_query: Query<(&mut Brick, &Transform, Entity)>,
-        paddle_query: Query<(&Paddle, &Transform, Entity)>,
-        wall_query: Query<(&Wall, &Transform, Entity)>,
+    // Bricks that jumped onto a ball shove it aside without touching its velocity,
+    // no further than the surfaces around it allow
+    #[allow(clippy::type_complexity)]
+    pub fn push_balls_out(
+        mut ball_query: Query<(&mut Transform, &Collider), (With<Ball>, Without<AttachedToPaddle>)>,
+        surfaces: Surfaces,
     ) {
-        for (mut ball, ball_transform) in &mut ball_query {
-            let mut found_collision_for_this_ball = false;
-            for (mut brick, brick_transform, brick_entity) in &mut brick_query {
-                if ball.last_col_entity != brick_entity {
-                    // Prevent double collision
-                    if let Some(normal_vec) =
-                        ColliderPlugin::collision_with_square(ball_transform, brick_transform)
-                    {
-                        // Update brick health
-                        match brick.level {
-                            BrickLevel::NONE => (),
-                            BrickLevel::LOW => brick.level = BrickLevel::NONE,
-                            BrickLevel::MID => brick.level = BrickLevel::LOW,
-                            BrickLevel::HIGH => brick.level = BrickLevel::MID,
-                            BrickLevel::SUPER => brick.level = BrickLevel::HIGH,
-                        };
-                        // Update ball
-                        ball.last_col_entity = brick_entity;
-                        ball.velocity =
-                            ball.velocity - 2.0 * normal_vec * normal_vec.dot(ball.velocity);
-                        found_collision_for_this_ball = true;
-                        break;
-                    };
-                }
-            }
-            if found_collision_for_this_ball {
+        for (mut transform, collider) in &mut ball_query {
+            let Collider::Circle { radius } = *collider else {
                 continue;
+            };
+            let start = transform.translation.truncate();
+            let push = surfaces.push_out(start, radius);
+            if push != Vec2::ZERO {
+                transform.translation += surfaces.clamp_motion(start, push, radius).extend(0.0);
             }
-            for (_, paddle_transform, paddle_entity) in &paddle_query {
-                if ball.last_col_entity != paddle_entity {
-                    // Prevent double collision
-                    if let Some(normal_vec) =
-                        ColliderPlugin::collision_with_square(ball_transform, paddle_transform)
-                    {
-                        ball.last_col_entity = paddle_entity;
-                        ball.velocity =
-                            ball.velocity - 2.0 * normal_vec * normal_vec.dot(ball.velocity);
-                        found_collision_for_this_ball = true;
-                        break;
-                    };
+        }
+    }
+
+    // Move each ball over the tick, reflecting off every surface it meets on the way.
+    // Fireballs fly through the bricks they can break instead of bouncing off them.
+    // Everything else a contact does is left to readers of `CollisionEvent`.
+    #[allow(clippy::type_complexity)]
+    pub fn move_and_collide(
+        mut ball_query: Query<
+            (Entity, &mut Ball, &mut Transform, &Collider, Has<Fireball>),
+            Without<AttachedToPaddle>,
+        >,
+        surfaces: Surfaces,
+        time: Res<Time>,
+        mut collision_events: EventWriter<CollisionEvent>,
+    ) {
+        for (ball_entity, mut ball, mut ball_transform, ball_collider, is_fireball) in
+            &mut ball_query
+        {
+            let Collider::Circle { radius } = *ball_collider else {
+                continue;
+            };
+            // Bricks already passed through this tick
+            let mut pierced = Vec::new();
+            let mut remaining = time.delta_seconds();
+            for _ in 0..MAX_CONTACTS_PER_TICK {
+                let start = ball_transform.translation.truncate();
+                let motion = ball.velocity * remaining;
+                let Some(hit) = surfaces.first_hit(start, motion, radius, remaining, &pierced)
+                else {
+                    ball_transform.translation += motion.extend(0.0);
+                    break;
+                };
+                // Advance to the contact point and bounce with the time left
+                ball_transform.translation += (motion * hit.time).extend(0.0);
+                remaining *= 1.0 - hit.time;
+                if is_fireball && surfaces.is_pierceable(hit.entity) {
+                    pierced.push(hit.entity);
+                } else {
+                    // Reflect relative to the surface so a moving brick pushes the ball away
+                    let relative = ball.velocity - hit.velocity;
+                    ball.velocity =
+                        relative - 2.0 * hit.normal * hit.normal.dot(relative) + hit.velocity;
                 }
+                collision_events.send(CollisionEvent {
+                    ball: ball_entity,
+                    other: hit.entity,
+                    kind: hit.kind,
+                    normal: hit.normal,
+                    point: ball_transform.translation.truncate() - hit.normal * radius,
+                });
             }
-            if found_collision_for_this_ball {
+        }
+    }
+
+    // Equal mass elastic bounce between overlapping balls, pushing them apart
+    // no further than the surfaces around them allow
+    fn collide_balls(
+        mut ball_query: Query<
+            (Entity, &mut Ball, &mut Transform, &Collider),
+            Without<AttachedToPaddle>,
+        >,
+        surfaces: Surfaces,
+        mut collision_events: EventWriter<CollisionEvent>,
+    ) {
+        let mut pairs = ball_query.iter_combinations_mut();
+        while let Some([first, second]) = pairs.fetch_next() {
+            let (entity_a, mut ball_a, mut transform_a, collider_a) = first;
+            let (entity_b, mut ball_b, mut transform_b, collider_b) = second;
+            let (Collider::Circle { radius: radius_a }, Collider::Circle { radius: radius_b }) =
+                (*collider_a, *collider_b)
+            else {
+                continue;
+            };
+            let offset = (transform_a.translation - transform_b.translation).truncate();
+            let distance = offset.length();
+            let overlap = radius_a + radius_b - distance;
+            // Balls on the same spot have no direction to separate along
+            if overlap <= 0.0 || distance == 0.0 {
                 continue;
             }
-            for (_, wall_transform, wall_entity) in &wall_query {
-                if ball.last_col_entity != wall_entity {
-                    // Prevent double collision
-                    if let Some(normal_vec) =
-                        ColliderPlugin::collision_with_square(ball_transform, wall_transform)
-                    {
-                        ball.last_col_entity = wall_entity;
-                        ball.velocity =
-                            ball.velocity - 2.0 * normal_vec * normal_vec.dot(ball.velocity);
-                        break;
-                    };
-                }
+            let normal = offset / distance;
+            let push = normal * overlap / 2.0;
+            let push_a = surfaces.clamp_motion(transform_a.translation.truncate(), push, radius_a);
+            let push_b = surfaces.clamp_motion(transform_b.translation.truncate(), -push, radius_b);
+            transform_a.translation += push_a.extend(0.0);
+            transform_b.translation += push_b.extend(0.0);
+
+            // Swap the velocity components along the normal, unless already separating
+            let approach = (ball_a.velocity - ball_b.velocity).dot(normal);
+            if approach >= 0.0 {
+                continue;
             }
+            ball_a.velocity -= normal * approach;
+            ball_b.velocity += normal * approach;
+            collision_events.send(CollisionEvent {
+                ball: entity_a,
+                other: entity_b,
+                kind: CollisionKind::Ball,
+                normal,
+                point: transform_a.translation.truncate() - normal * radius_a,
+            });
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HALF: Vec2 = Vec2::new(20.0, 10.0);
+
+    fn sweep_box(start: Vec2, motion: Vec2) -> Option<(f32, Vec2)> {
+        ColliderPlugin::sweep_circle_box(start, motion, 10.0, Vec2::ZERO, HALF)
+    }
+
+    fn assert_hit(hit: Option<(f32, Vec2)>, time: f32, normal: Vec2) {
+        let (hit_time, hit_normal) = hit.expect("expected a hit");
+        assert!(
+            (hit_time - time).abs() < 1e-4,
+            "time {} != {}",
+            hit_time,
+            time
+        );
+        assert!(
+            hit_normal.abs_diff_eq(normal, 1e-4),
+            "normal {} != {}",
+            hit_normal,
+            normal
+        );
+    }
+
+    #[test]
+    fn box_face_hit() {
+        let hit = sweep_box(Vec2::new(5.0, -50.0), Vec2::new(0.0, 100.0));
+        assert_hit(hit, 0.3, Vec2::NEG_Y);
+    }
+
+    #[test]
+    fn box_missed() {
+        assert!(sweep_box(Vec2::new(5.0, -50.0), Vec2::new(0.0, 20.0)).is_none());
+        assert!(sweep_box(Vec2::new(5.0, -50.0), Vec2::new(0.0, -100.0)).is_none());
+        assert!(sweep_box(Vec2::new(-50.0, 21.0), Vec2::new(100.0, 0.0)).is_none());
+    }
+
+    #[test]
+    fn box_corner_hit() {
+        // Heading straight at the bottom left corner
+        let hit = sweep_box(Vec2::new(-50.0, -40.0), Vec2::new(60.0, 60.0));
+        let time = 0.5 - 10.0 / (60.0 * std::f32::consts::SQRT_2);
+        assert_hit(hit, time, Vec2::new(-1.0, -1.0).normalize());
+    }
+
+    #[test]
+    fn box_grazing_hit() {
+        // Skimming over the top left corner only clips the rounded edge
+        let hit = sweep_box(Vec2::new(-50.0, 19.0), Vec2::new(100.0, 0.0));
+        let (time, normal) = hit.expect("expected a hit");
+        let contact = Vec2::new(-50.0 + 100.0 * time, 19.0);
+        assert!(time > 0.2 && time < 0.3);
+        assert!(normal.x < 0.0 && normal.y > 0.0);
+        assert!(((contact - Vec2::new(-20.0, 10.0)).length() - 10.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn starting_inside_box() {
+        // Pushed out through the nearest face, but only when moving further in
+        let start = Vec2::new(3.0, 5.0);
+        assert_hit(sweep_box(start, Vec2::new(0.0, -10.0)), 0.0, Vec2::Y);
+        assert!(sweep_box(start, Vec2::new(0.0, 10.0)).is_none());
+    }
+
+    #[test]
+    fn starting_against_box() {
+        let start = Vec2::new(0.0, -19.0);
+        assert_hit(sweep_box(start, Vec2::new(1.0, 10.0)), 0.0, Vec2::NEG_Y);
+        assert!(sweep_box(start, Vec2::new(1.0, -10.0)).is_none());
+    }
+
+    #[test]
+    fn rotated_box_face_hit() {
+        // Straight at the top face of a box turned by 45 degrees
+        let obb = Collider::Obb {
+            half_extents: HALF,
+            rotation: std::f32::consts::FRAC_PI_4,
+        };
+        let normal = Vec2::new(-1.0, 1.0).normalize();
+        let hit = ColliderPlugin::sweep_circle(
+            normal * 50.0,
+            normal * -100.0,
+            10.0,
+            &obb,
+            &Transform::IDENTITY,
+        );
+        assert_hit(hit, 0.3, normal);
+    }
+
+    #[test]
+    fn rotated_box_corner_hit() {
+        // The entity's turn adds to the box's own, a quarter turn in all
+        let obb = Collider::Obb {
+            half_extents: HALF,
+            rotation: std::f32::consts::FRAC_PI_4,
+        };
+        let transform = Transform::from_xyz(5.0, -5.0, 0.0)
+            .with_rotation(Quat::from_rotation_z(std::f32::consts::FRAC_PI_4));
+        // The top right corner of the unturned box ends up at the top left
+        let corner = Vec2::new(-HALF.y, HALF.x) + Vec2::new(5.0, -5.0);
+        let normal = Vec2::new(-1.0, 1.0).normalize();
+        let hit = ColliderPlugin::sweep_circle(
+            corner + normal * 40.0,
+            normal * -60.0,
+            10.0,
+            &obb,
+            &transform,
+        );
+        assert_hit(hit, 0.5, normal);
+    }
+
+    #[test]
+    fn push_out_of_box_edge() {
+        let push =
+            ColliderPlugin::push_circle_out_of_box(Vec2::new(25.0, 0.0), 10.0, Vec2::ZERO, HALF);
+        assert_eq!(push, Some(Vec2::new(5.0, 0.0)));
+        let clear =
+            ColliderPlugin::push_circle_out_of_box(Vec2::new(30.0, 0.0), 10.0, Vec2::ZERO, HALF);
+        assert!(clear.is_none());
+    }
+
+    #[test]
+    fn push_out_of_box_centre_inside() {
+        // Out through the top, the nearest face
+        let push =
+            ColliderPlugin::push_circle_out_of_box(Vec2::new(3.0, 5.0), 10.0, Vec2::ZERO, HALF);
+        assert_eq!(push, Some(Vec2::new(0.0, 15.0)));
+    }
+
+    #[test]
+    fn capsule_touches_box() {
+        let box_collider = Collider::Aabb { half_extents: HALF };
+        let capsule = Collider::Capsule {
+            half_length: 14.0,
+            radius: 8.0,
+        };
+        let at = |x: f32, y: f32| Transform::from_xyz(x, y, 0.0);
+        // Lying across the top face
+        assert!(capsule.overlaps(&at(0.0, 17.0), &box_collider, &Transform::IDENTITY));
+        assert!(box_collider.overlaps(&Transform::IDENTITY, &capsule, &at(0.0, 17.0)));
+        // End cap over the top right corner
+        assert!(capsule.overlaps(&at(40.0, 15.0), &box_collider, &Transform::IDENTITY));
+        assert!(!capsule.overlaps(&at(0.0, 19.0), &box_collider, &Transform::IDENTITY));
+    }
+
+    #[test]
+    fn capsule_clears_box_corner() {
+        // The bounding boxes overlap, the rounded end does not reach the corner
+        let box_collider = Collider::Aabb { half_extents: HALF };
+        let capsule = Collider::Capsule {
+            half_length: 14.0,
+            radius: 8.0,
+        };
+        let transform = Transform::from_xyz(40.0, 16.0, 0.0);
+        assert!(capsule.bounds_overlap(&transform, &box_collider, &Transform::IDENTITY));
+        assert!(!capsule.overlaps(&transform, &box_collider, &Transform::IDENTITY));
+    }
+
+    #[test]
+    fn point_hit() {
+        let hit = ColliderPlugin::sweep_circle_point(
+            Vec2::new(-50.0, 0.0),
+            Vec2::new(100.0, 0.0),
+            10.0,
+            Vec2::ZERO,
+        );
+        assert_hit(hit, 0.4, Vec2::NEG_X);
+    }
+
+    #[test]
+    fn point_grazing_hit() {
+        // Passing exactly one radius away only touches
+        let hit = ColliderPlugin::sweep_circle_point(
+            Vec2::new(-50.0, 10.0),
+            Vec2::new(100.0, 0.0),
+            10.0,
+            Vec2::ZERO,
+        );
+        assert_hit(hit, 0.5, Vec2::Y);
+    }
+
+    #[test]
+    fn point_missed() {
+        let sweep = |start: Vec2, motion: Vec2| {
+            ColliderPlugin::sweep_circle_point(start, motion, 10.0, Vec2::ZERO)
+        };
+        assert!(sweep(Vec2::new(-50.0, 0.0), Vec2::new(30.0, 0.0)).is_none());
+        assert!(sweep(Vec2::new(-50.0, 0.0), Vec2::new(-100.0, 0.0)).is_none());
+        assert!(sweep(Vec2::new(-50.0, 11.0), Vec2::new(100.0, 0.0)).is_none());
+        assert!(sweep(Vec2::new(-50.0, 0.0), Vec2::ZERO).is_none());
+    }
+}
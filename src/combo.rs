@@ -0,0 +1,77 @@
+use bevy::prelude::*;
+
+use crate::ball::BallPlugin;
+use crate::brick::{Brick, Indestructible, Locked};
+use crate::events::{BallLostEvent, CollisionEvent, CollisionKind};
+use crate::schedule::InGameSet;
+use crate::state::GameState;
+
+// Brick hits needed for each step of the multiplier
+const HITS_PER_STEP: u32 = 4;
+const MAX_MULTIPLIER: u32 = 5;
+
+// Breakable bricks hit since a ball last touched the paddle
+#[derive(Resource, Default)]
+pub struct Combo {
+    pub hits: u32,
+    // Longest combo of the run
+    pub best: u32,
+}
+
+impl Combo {
+    pub fn multiplier(&self) -> u32 {
+        (1 + self.hits / HITS_PER_STEP).min(MAX_MULTIPLIER)
+    }
+}
+
+pub struct ComboPlugin;
+
+impl Plugin for ComboPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Combo>()
+            .add_systems(
+                FixedUpdate,
+                ComboPlugin::count_hits.in_set(InGameSet::CollisionResponse),
+            )
+            // Any ball falling out ends the combo, even with others still in play
+            .add_systems(
+                FixedUpdate,
+                ComboPlugin::break_combo
+                    .run_if(on_event::<BallLostEvent>())
+                    .after(BallPlugin::handle_ball_loss)
+                    .in_set(InGameSet::DespawnEntities),
+            )
+            // The best combo is kept for the end screen, only a new run resets it
+            .add_systems(OnEnter(GameState::PrepGame), ComboPlugin::break_combo)
+            .add_systems(OnEnter(GameState::MainMenu), ComboPlugin::reset_combo)
+            .add_systems(OnExit(GameState::EndMenu), ComboPlugin::reset_combo);
+    }
+}
+
+impl ComboPlugin {
+    // Hits on bricks that can take damage build the combo, touching the paddle ends it
+    fn count_hits(
+        mut collision_events: EventReader<CollisionEvent>,
+        breakable_query: Query<&Brick, (Without<Indestructible>, Without<Locked>)>,
+        mut combo: ResMut<Combo>,
+    ) {
+        for event in collision_events.read() {
+            match event.kind {
+                CollisionKind::Brick if breakable_query.contains(event.other) => {
+                    combo.hits += 1;
+                    combo.best = combo.best.max(combo.hits);
+                }
+                CollisionKind::Paddle if combo.hits > 0 => combo.hits = 0,
+                _ => (),
+            }
+        }
+    }
+
+    fn break_combo(mut combo: ResMut<Combo>) {
+        combo.hits = 0;
+    }
+
+    fn reset_combo(mut combo: ResMut<Combo>) {
+        *combo = Combo::default();
+    }
+}
@@ -3,12 +3,62 @@ use bevy::prelude::*;
 #[derive(Event, Debug)]
 pub struct UpdateScoreEvent {
     pub score: u32,
+    // Combo multiplier when the points were scored
+    pub multiplier: u32,
+}
+
+// A brick was broken and is being despawned
+#[derive(Event, Debug)]
+pub struct BrickDestroyedEvent {
+    // Hit points the brick was spawned with
+    pub hit_points: u32,
+    pub position: Vec3,
+}
+
+// A ball left the arena and is being despawned, whether or not others are still in play
+#[derive(Event, Debug)]
+pub struct BallLostEvent;
+
+// Every ball in play splits into several
+#[derive(Event, Debug)]
+pub struct MultiBallEvent;
+
+// Put up a safety barrier below the paddle, lasting until either limit runs out
+#[derive(Event, Debug)]
+pub struct GrantBarrierEvent {
+    pub bounces: u32,
+    pub seconds: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CollisionKind {
+    Ball,
+    Brick,
+    Paddle,
+    Wall,
+}
+
+// A ball touched another collider, sent after the ball has been reflected
+#[derive(Event, Debug)]
+pub struct CollisionEvent {
+    pub ball: Entity,
+    pub other: Entity,
+    pub kind: CollisionKind,
+    // Surface normal pointing towards the ball
+    pub normal: Vec2,
+    // Contact point on the surface
+    pub point: Vec2,
 }
 
 pub struct EventPlugin;
 
 impl Plugin for EventPlugin {
     fn build(&self, app: &mut App) {
-        app.add_event::<UpdateScoreEvent>();
+        app.add_event::<UpdateScoreEvent>()
+            .add_event::<CollisionEvent>()
+            .add_event::<BrickDestroyedEvent>()
+            .add_event::<BallLostEvent>()
+            .add_event::<MultiBallEvent>()
+            .add_event::<GrantBarrierEvent>();
     }
 }
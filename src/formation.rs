@@ -0,0 +1,260 @@
+use std::f32::consts::TAU;
+
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use crate::brick::Brick;
+use crate::schedule::InGameSet;
+
+// Path a formation of bricks follows around the bricks' home positions
+#[derive(Deserialize, Debug, Clone)]
+pub enum Patrol {
+    // Back and forth along x at a constant speed, up to `distance` either side of home
+    Sweep {
+        distance: f32,
+        speed: f32,
+    },
+    // Sine wave with its own amplitude on each axis
+    Wave {
+        amplitude: (f32, f32),
+        period: f32,
+    },
+    // Invaders march: `steps` sideways steps every `interval` seconds, then a drop,
+    // until the formation has come down by `max_drop`
+    Descend {
+        step: f32,
+        steps: u32,
+        interval: f32,
+        drop: f32,
+        max_drop: f32,
+    },
+}
+
+impl Patrol {
+    // Offset from home after `elapsed` seconds
+    pub fn offset(&self, elapsed: f32) -> Vec2 {
+        match *self {
+            Patrol::Sweep { distance, speed } => {
+                if distance <= 0.0 {
+                    return Vec2::ZERO;
+                }
+                // Triangle wave: out to the right, across to the left and back home
+                let phase = (elapsed * speed / distance).rem_euclid(4.0);
+                let x = if phase < 1.0 {
+                    phase
+                } else if phase < 3.0 {
+                    2.0 - phase
+                } else {
+                    phase - 4.0
+                };
+                Vec2::new(x * distance, 0.0)
+            }
+            Patrol::Wave { amplitude, period } => {
+                Vec2::from(amplitude) * (elapsed * TAU / period).sin()
+            }
+            Patrol::Descend {
+                step,
+                steps,
+                interval,
+                drop,
+                max_drop,
+            } => {
+                let steps = steps.max(1);
+                let moves = (elapsed / interval) as u32;
+                let pass = moves / steps;
+                let along = moves % steps;
+                // Every other pass marches back the way it came
+                let x = if pass.is_multiple_of(2) {
+                    along
+                } else {
+                    steps - along
+                };
+                Vec2::new(step * x as f32, -(drop * pass as f32).min(max_drop))
+            }
+        }
+    }
+
+    // Marches jump from one position to the next instead of moving in between
+    fn is_discrete(&self) -> bool {
+        matches!(self, Patrol::Descend { .. })
+    }
+
+    // Smallest and largest offsets the patrol ever reaches
+    pub fn reach(&self) -> (Vec2, Vec2) {
+        match *self {
+            Patrol::Sweep { distance, .. } => (
+                Vec2::new(-distance.abs(), 0.0),
+                Vec2::new(distance.abs(), 0.0),
+            ),
+            Patrol::Wave { amplitude, .. } => {
+                let amplitude = Vec2::from(amplitude).abs();
+                (-amplitude, amplitude)
+            }
+            Patrol::Descend {
+                step,
+                steps,
+                max_drop,
+                ..
+            } => (
+                Vec2::new((step * steps as f32).min(0.0), -max_drop),
+                Vec2::new((step * steps as f32).max(0.0), 0.0),
+            ),
+        }
+    }
+}
+
+// Shared clock and position of a group of bricks moving together
+#[derive(Component)]
+pub struct Formation {
+    patrol: Patrol,
+    elapsed: f32,
+    offset: Vec2,
+    velocity: Vec2,
+}
+
+impl Formation {
+    pub fn new(patrol: Patrol) -> Formation {
+        Formation {
+            patrol,
+            elapsed: 0.0,
+            offset: Vec2::ZERO,
+            velocity: Vec2::ZERO,
+        }
+    }
+
+    // Smallest and largest offsets the formation ever reaches
+    pub fn reach(&self) -> (Vec2, Vec2) {
+        self.patrol.reach()
+    }
+}
+
+// Brick placed relative to its formation. Bricks are not children of the formation:
+// collisions read `Transform` during the fixed tick, before any transform propagation.
+#[derive(Component)]
+pub struct FormationMember {
+    pub formation: Entity,
+    pub home: Vec2,
+    // Velocity over the last tick, balls bounce off the brick as it moves
+    pub velocity: Vec2,
+}
+
+pub struct FormationPlugin;
+
+impl Plugin for FormationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            FixedUpdate,
+            (
+                FormationPlugin::advance_formations,
+                FormationPlugin::move_members,
+            )
+                .chain()
+                .in_set(InGameSet::EntityUpdates),
+        );
+    }
+}
+
+impl FormationPlugin {
+    fn advance_formations(mut query: Query<&mut Formation>, time: Res<Time>) {
+        let delta = time.delta_seconds();
+        if delta <= 0.0 {
+            return;
+        }
+        for mut formation in &mut query {
+            formation.elapsed += delta;
+            let offset = formation.patrol.offset(formation.elapsed);
+            // A jump has no speed to pass on, balls in the way are pushed out instead
+            formation.velocity = if formation.patrol.is_discrete() {
+                Vec2::ZERO
+            } else {
+                (offset - formation.offset) / delta
+            };
+            formation.offset = offset;
+        }
+    }
+
+    fn move_members(
+        mut member_query: Query<(&mut Transform, &mut FormationMember), With<Brick>>,
+        formation_query: Query<&Formation>,
+    ) {
+        for (mut transform, mut member) in &mut member_query {
+            let Ok(formation) = formation_query.get(member.formation) else {
+                continue;
+            };
+            let position = member.home + formation.offset;
+            transform.translation.x = position.x;
+            transform.translation.y = position.y;
+            member.velocity = formation.velocity;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bevy::ecs::system::RunSystemOnce;
+
+    use super::*;
+    use crate::ball::Ball;
+    use crate::brick::{BrickKinds, LOW_KIND};
+    use crate::broadphase::{BroadPhase, BroadPhasePlugin};
+    use crate::collider::{Collider, ColliderPlugin};
+    use crate::events::CollisionEvent;
+
+    const TICK: f32 = 1.0 / 120.0;
+
+    #[test]
+    fn descend_step_leaves_ball_speed_alone() {
+        let mut world = World::new();
+        world.init_resource::<BroadPhase>();
+        world.init_resource::<Events<CollisionEvent>>();
+        let mut time = Time::<()>::default();
+        time.advance_by(Duration::from_secs_f32(TICK));
+        world.insert_resource(time);
+
+        // Half a tick before the first step of 30 to the right
+        let mut formation = Formation::new(Patrol::Descend {
+            step: 30.0,
+            steps: 4,
+            interval: 0.5,
+            drop: 20.0,
+            max_drop: 60.0,
+        });
+        formation.elapsed = 0.5 - TICK / 2.0;
+        let formation = world.spawn(formation).id();
+        let kinds = BrickKinds::default();
+        world.spawn((
+            Brick::new(kinds.id(LOW_KIND).unwrap(), &kinds),
+            Transform::default(),
+            Collider::Aabb {
+                half_extents: Vec2::new(40.0, 15.0),
+            },
+            FormationMember {
+                formation,
+                home: Vec2::ZERO,
+                velocity: Vec2::ZERO,
+            },
+        ));
+        // Clear of the brick's right face until the step, heading back towards it
+        let velocity = Vec2::new(-200.0, 100.0);
+        let ball = world
+            .spawn((
+                Ball { velocity },
+                Transform::from_xyz(60.0, 0.0, 0.0),
+                Collider::Circle { radius: 10.0 },
+            ))
+            .id();
+
+        world.run_system_once(FormationPlugin::advance_formations);
+        world.run_system_once(FormationPlugin::move_members);
+        world.run_system_once(BroadPhasePlugin::sync_broadphase);
+        world.run_system_once(ColliderPlugin::push_balls_out);
+        world.run_system_once(ColliderPlugin::move_and_collide);
+
+        let speed = world.get::<Ball>(ball).unwrap().velocity.length();
+        assert!((speed - velocity.length()).abs() < 1e-3, "speed {}", speed);
+        // Pushed out past the brick's new right face at 70
+        assert!(world.get::<Transform>(ball).unwrap().translation.x >= 80.0 - 1e-3);
+    }
+}
@@ -0,0 +1,155 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::storage::{self, StorageError};
+
+// Places kept in the table
+pub const MAX_HIGH_SCORES: usize = 10;
+const HIGH_SCORE_FILE: &str = "highscores.ron";
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct HighScore {
+    pub name: String,
+    pub score: u32,
+}
+
+// Best scores of past runs, highest first
+#[derive(Resource, Serialize, Deserialize, Debug, Default)]
+pub struct HighScores {
+    pub entries: Vec<HighScore>,
+}
+
+impl HighScores {
+    fn load() -> Result<HighScores, StorageError> {
+        let mut high_scores: HighScores = storage::load(&storage::data_file(HIGH_SCORE_FILE)?)?;
+        // A file edited by hand may be out of order or too long
+        high_scores
+            .entries
+            .sort_by_key(|entry| std::cmp::Reverse(entry.score));
+        high_scores.entries.truncate(MAX_HIGH_SCORES);
+        Ok(high_scores)
+    }
+
+    fn save(&self) -> Result<(), StorageError> {
+        storage::save(&storage::data_file(HIGH_SCORE_FILE)?, self)
+    }
+
+    pub fn best(&self) -> u32 {
+        self.entries.first().map_or(0, |entry| entry.score)
+    }
+
+    // Whether a score would make it into the table
+    pub fn qualifies(&self, score: u32) -> bool {
+        score > 0
+            && (self.entries.len() < MAX_HIGH_SCORES
+                || self.entries.last().is_some_and(|entry| score > entry.score))
+    }
+
+    // Put a score in the table and write it out, returns its place counting from 1
+    pub fn record(&mut self, name: String, score: u32) -> Option<usize> {
+        let rank = self.insert(name, score)?;
+        if let Err(error) = self.save() {
+            error!("Could not save high scores: {}", error);
+        }
+        Some(rank)
+    }
+
+    // Ties rank below the scores already there
+    fn insert(&mut self, name: String, score: u32) -> Option<usize> {
+        if !self.qualifies(score) {
+            return None;
+        }
+        let index = self
+            .entries
+            .iter()
+            .position(|entry| score > entry.score)
+            .unwrap_or(self.entries.len());
+        self.entries.insert(index, HighScore { name, score });
+        self.entries.truncate(MAX_HIGH_SCORES);
+        Some(index + 1)
+    }
+}
+
+pub struct HighScorePlugin;
+
+impl Plugin for HighScorePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<HighScores>()
+            .add_systems(Startup, HighScorePlugin::load_high_scores);
+    }
+}
+
+impl HighScorePlugin {
+    // A missing or broken file leaves the table empty
+    fn load_high_scores(mut high_scores: ResMut<HighScores>) {
+        match HighScores::load() {
+            Ok(loaded) => *high_scores = loaded,
+            Err(error) if error.is_missing() => (),
+            Err(error) => warn!("Starting with no high scores: {}", error),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table(scores: &[u32]) -> HighScores {
+        HighScores {
+            entries: scores
+                .iter()
+                .enumerate()
+                .map(|(index, &score)| HighScore {
+                    name: format!("P{}", index + 1),
+                    score,
+                })
+                .collect(),
+        }
+    }
+
+    fn names(high_scores: &HighScores) -> Vec<&str> {
+        high_scores
+            .entries
+            .iter()
+            .map(|entry| entry.name.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn tie_ranks_below_existing_score() {
+        let mut high_scores = table(&[50, 30, 10]);
+        assert_eq!(high_scores.insert(String::from("New"), 30), Some(3));
+        assert_eq!(names(&high_scores), ["P1", "P2", "New", "P3"]);
+    }
+
+    #[test]
+    fn tie_with_every_score() {
+        let mut high_scores = table(&[20, 20]);
+        assert_eq!(high_scores.insert(String::from("New"), 20), Some(3));
+        assert_eq!(names(&high_scores), ["P1", "P2", "New"]);
+    }
+
+    #[test]
+    fn tie_with_last_place_of_full_table_does_not_qualify() {
+        let mut high_scores = table(&[100, 90, 80, 70, 60, 50, 40, 30, 20, 10]);
+        assert_eq!(high_scores.insert(String::from("New"), 10), None);
+        assert_eq!(high_scores.entries.len(), MAX_HIGH_SCORES);
+        assert_eq!(high_scores.entries.last().unwrap().name, "P10");
+    }
+
+    #[test]
+    fn full_table_drops_last_place() {
+        let mut high_scores = table(&[100, 90, 80, 70, 60, 50, 40, 30, 20, 10]);
+        assert_eq!(high_scores.insert(String::from("New"), 20), Some(10));
+        assert_eq!(high_scores.entries.len(), MAX_HIGH_SCORES);
+        assert_eq!(high_scores.entries[8].name, "P9");
+        assert_eq!(high_scores.entries[9].name, "New");
+    }
+
+    #[test]
+    fn zero_score_is_not_recorded() {
+        let mut high_scores = table(&[]);
+        assert_eq!(high_scores.insert(String::from("New"), 0), None);
+        assert!(high_scores.entries.is_empty());
+    }
+}
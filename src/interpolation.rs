@@ -0,0 +1,53 @@
+use bevy::prelude::*;
+
+// Entity moved by the fixed timestep, drawn between its last two tick positions
+#[derive(Component)]
+pub struct Interpolated {
+    previous: Vec3,
+    current: Vec3,
+}
+
+impl Interpolated {
+    pub fn new(translation: Vec3) -> Interpolated {
+        Interpolated {
+            previous: translation,
+            current: translation,
+        }
+    }
+}
+
+pub struct InterpolationPlugin;
+
+impl Plugin for InterpolationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(FixedFirst, InterpolationPlugin::restore_tick_translation)
+            .add_systems(FixedLast, InterpolationPlugin::record_tick_translation)
+            .add_systems(Update, InterpolationPlugin::interpolate_translation);
+    }
+}
+
+impl InterpolationPlugin {
+    // Gameplay must start each tick from where the last tick left off
+    fn restore_tick_translation(mut query: Query<(&mut Transform, &mut Interpolated)>) {
+        for (mut transform, mut interpolated) in &mut query {
+            interpolated.previous = interpolated.current;
+            transform.translation = interpolated.current;
+        }
+    }
+
+    fn record_tick_translation(mut query: Query<(&Transform, &mut Interpolated)>) {
+        for (transform, mut interpolated) in &mut query {
+            interpolated.current = transform.translation;
+        }
+    }
+
+    fn interpolate_translation(
+        mut query: Query<(&mut Transform, &Interpolated)>,
+        fixed_time: Res<Time<Fixed>>,
+    ) {
+        let fraction = fixed_time.overstep_fraction();
+        for (mut transform, interpolated) in &mut query {
+            transform.translation = interpolated.previous.lerp(interpolated.current, fraction);
+        }
+    }
+}
@@ -0,0 +1,183 @@
+use bevy::prelude::*;
+
+use crate::bindings::{Action, Actions};
+use crate::brick::{Brick, Indestructible, Locked};
+use crate::broadphase::{BroadPhase, BroadPhasePlugin};
+use crate::collider::Collider;
+use crate::interpolation::Interpolated;
+use crate::paddle::Paddle;
+use crate::powerup::ActiveEffects;
+use crate::schedule::InGameSet;
+use crate::state::GameState;
+use crate::wall::Wall;
+
+// Seconds between two shots
+const FIRE_COOLDOWN: f32 = 0.3;
+const BOLT_SIZE: Vec3 = Vec3::new(4.0, 14.0, 1.0);
+const BOLT_SPEED: f32 = 600.0;
+const BOLT_COLOR: Color = Color::rgb(1.0, 0.2, 0.2);
+// Distance of the cannons from the ends of the paddle
+const CANNON_INSET: f32 = 8.0;
+const BOLT_Z: f32 = 0.5;
+
+// Trigger state and fire rate of the paddle's cannons
+#[derive(Resource)]
+struct LaserCannon {
+    trigger_held: bool,
+    cooldown: Timer,
+}
+
+impl Default for LaserCannon {
+    fn default() -> Self {
+        let mut cooldown = Timer::from_seconds(FIRE_COOLDOWN, TimerMode::Once);
+        // Ready to fire as soon as the power-up is caught
+        cooldown.tick(cooldown.duration());
+        LaserCannon {
+            trigger_held: false,
+            cooldown,
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct LaserBolt;
+
+#[derive(Bundle)]
+struct LaserBoltBundle {
+    bolt: LaserBolt,
+    sprite: SpriteBundle,
+    collider: Collider,
+    interpolated: Interpolated,
+}
+
+impl LaserBoltBundle {
+    fn new(position: Vec2) -> LaserBoltBundle {
+        let translation = position.extend(BOLT_Z);
+        LaserBoltBundle {
+            bolt: LaserBolt,
+            sprite: SpriteBundle {
+                sprite: Sprite {
+                    color: BOLT_COLOR,
+                    ..default()
+                },
+                transform: Transform {
+                    translation,
+                    scale: BOLT_SIZE,
+                    ..default()
+                },
+                ..default()
+            },
+            collider: Collider::aabb(BOLT_SIZE),
+            interpolated: Interpolated::new(translation),
+        }
+    }
+}
+
+pub struct LaserPlugin;
+
+impl Plugin for LaserPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<LaserCannon>()
+            .add_systems(
+                Update,
+                LaserPlugin::handle_input.in_set(InGameSet::UserInput),
+            )
+            .add_systems(
+                FixedUpdate,
+                (LaserPlugin::fire, LaserPlugin::move_bolts)
+                    .chain()
+                    .in_set(InGameSet::EntityUpdates),
+            )
+            .add_systems(
+                FixedUpdate,
+                LaserPlugin::hit
+                    .after(BroadPhasePlugin::sync_broadphase)
+                    .in_set(InGameSet::CollisionDetection),
+            )
+            .add_systems(OnEnter(GameState::MainMenu), LaserPlugin::despawn_all_bolts)
+            .add_systems(OnExit(GameState::EndMenu), LaserPlugin::despawn_all_bolts)
+            .add_systems(
+                OnExit(GameState::LevelCleared),
+                LaserPlugin::despawn_all_bolts,
+            );
+    }
+}
+
+impl LaserPlugin {
+    fn handle_input(actions: Actions, mut laser_cannon: ResMut<LaserCannon>) {
+        laser_cannon.trigger_held = actions.pressed(Action::Launch);
+    }
+
+    // Shoot from both ends of the paddle while the laser effect runs
+    fn fire(
+        mut commands: Commands,
+        mut laser_cannon: ResMut<LaserCannon>,
+        paddle_query: Query<(&Transform, &Collider), With<Paddle>>,
+        active_effects: Res<ActiveEffects>,
+        time: Res<Time>,
+    ) {
+        laser_cannon.cooldown.tick(time.delta());
+        if !active_effects.has_laser()
+            || !laser_cannon.trigger_held
+            || !laser_cannon.cooldown.finished()
+        {
+            return;
+        }
+        let Ok((paddle_transform, paddle_collider)) = paddle_query.get_single() else {
+            return;
+        };
+        let (min, max) = paddle_collider.bounds(paddle_transform);
+        let y = max.y + BOLT_SIZE.y / 2.0;
+        commands.spawn(LaserBoltBundle::new(Vec2::new(min.x + CANNON_INSET, y)));
+        commands.spawn(LaserBoltBundle::new(Vec2::new(max.x - CANNON_INSET, y)));
+        laser_cannon.cooldown.reset();
+    }
+
+    fn move_bolts(mut query: Query<&mut Transform, With<LaserBolt>>, time: Res<Time>) {
+        for mut transform in &mut query {
+            transform.translation.y += BOLT_SPEED * time.delta_seconds();
+        }
+    }
+
+    // A bolt is spent on the first brick or wall it touches
+    #[allow(clippy::type_complexity)]
+    fn hit(
+        mut commands: Commands,
+        bolt_query: Query<(Entity, &Transform, &Collider), With<LaserBolt>>,
+        mut brick_query: Query<(&mut Brick, &Transform, &Collider), Without<LaserBolt>>,
+        protected_query: Query<(), Or<(With<Indestructible>, With<Locked>)>>,
+        wall_query: Query<(&Transform, &Collider), (With<Wall>, Without<LaserBolt>)>,
+        broadphase: Res<BroadPhase>,
+    ) {
+        for (bolt_entity, bolt_transform, bolt_collider) in &bolt_query {
+            let (min, max) = bolt_collider.bounds(bolt_transform);
+            for entity in broadphase.query(min, max) {
+                if let Ok((mut brick, transform, collider)) = brick_query.get_mut(entity) {
+                    // Broken bricks are only waiting to be despawned
+                    if brick.is_broken()
+                        || !bolt_collider.bounds_overlap(bolt_transform, collider, transform)
+                    {
+                        continue;
+                    }
+                    if !protected_query.contains(entity) {
+                        brick.damage(1);
+                    }
+                } else if let Ok((transform, collider)) = wall_query.get(entity) {
+                    if !bolt_collider.bounds_overlap(bolt_transform, collider, transform) {
+                        continue;
+                    }
+                } else {
+                    continue;
+                }
+                commands.entity(bolt_entity).despawn();
+                break;
+            }
+        }
+    }
+
+    fn despawn_all_bolts(mut commands: Commands, query: Query<Entity, With<LaserBolt>>) {
+        for entity in &query {
+            commands.entity(entity).despawn();
+        }
+    }
+}
@@ -0,0 +1,681 @@
+use std::collections::BTreeMap;
+
+use bevy::asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext};
+use bevy::prelude::*;
+use bevy::utils::BoxedFuture;
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::brick::{
+    BrickGrid, BrickKind, BrickKindId, BrickKinds, GridCell, BRICK_SIZE, EXPLOSION_RADIUS,
+    HIGH_KIND, HIVE_INTERVAL, LOW_KIND, MID_KIND, REGENERATION_DELAY, SUPER_KIND,
+};
+use crate::formation::Patrol;
+use crate::state::GameState;
+use crate::wall;
+
+// Cell symbol that leaves a grid cell empty
+const EMPTY_CELL: char = '.';
+// Cell symbol of a low brick that explodes when broken
+const EXPLOSIVE_CELL: char = 'X';
+// Cell symbol of a brick that never breaks
+const STEEL_CELL: char = '#';
+// Cell symbol of a high brick that heals when left alone
+const REGENERATING_CELL: char = 'R';
+// Cell symbol of a high brick that grows low bricks into the empty cells around it
+const HIVE_CELL: char = 'Q';
+// Key bricks are '1' to '9', breaking all of a rank unlocks locked bricks 'a' to 'i' of that rank
+const KEY_CELLS: std::ops::RangeInclusive<char> = '1'..='9';
+const LOCKED_CELLS: std::ops::RangeInclusive<char> = 'a'..='i';
+
+pub struct LevelPlugin;
+
+impl Plugin for LevelPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<Level>()
+            .init_asset::<Campaign>()
+            .register_asset_loader(LevelLoader)
+            .register_asset_loader(CampaignLoader)
+            .insert_resource(CurrentLevel(0))
+            .add_systems(OnExit(GameState::LevelCleared), LevelPlugin::next_level)
+            .add_systems(OnEnter(GameState::MainMenu), LevelPlugin::reset_level)
+            .add_systems(OnExit(GameState::EndMenu), LevelPlugin::reset_level);
+    }
+}
+
+impl LevelPlugin {
+    fn next_level(mut current_level: ResMut<CurrentLevel>) {
+        current_level.0 += 1;
+    }
+
+    fn reset_level(mut current_level: ResMut<CurrentLevel>) {
+        current_level.0 = 0;
+    }
+}
+
+// Handle to the campaign the levels are played from
+#[derive(Resource)]
+pub struct CampaignHandle(pub Handle<Campaign>);
+
+// Index of the level being played in the campaign
+#[derive(Resource)]
+pub struct CurrentLevel(pub usize);
+
+// Ordered list of levels in a run
+#[derive(Asset, TypePath, Debug)]
+pub struct Campaign {
+    #[dependency]
+    pub levels: Vec<Handle<Level>>,
+}
+
+impl Campaign {
+    pub fn level(&self, index: usize) -> Option<&Handle<Level>> {
+        self.levels.get(index)
+    }
+
+    pub fn is_last(&self, index: usize) -> bool {
+        index + 1 >= self.levels.len()
+    }
+}
+
+#[derive(Asset, TypePath, Debug)]
+pub struct Level {
+    pub name: String,
+    pub bricks: Vec<LevelBrick>,
+    pub grid: BrickGrid,
+    // Built-in kinds and the ones declared by the level
+    pub kinds: BrickKinds,
+    // Patrol of each group of bricks moving together
+    pub formations: Vec<Patrol>,
+}
+
+#[derive(Debug, Clone)]
+pub struct LevelBrick {
+    pub kind: BrickKindId,
+    pub position: Vec3,
+    pub cell: GridCell,
+    pub role: Option<BrickRole>,
+    // Index into the level's formations, for moving bricks
+    pub formation: Option<usize>,
+}
+
+// What a brick does on top of losing hit points when hit
+#[derive(Debug, Clone)]
+pub enum BrickRole {
+    // Damages the bricks in reach when broken
+    Explosive { radius: f32 },
+    Indestructible,
+    // Heals one hit point after `delay` seconds without damage
+    Regenerating { delay: f32 },
+    // Spawns a brick of kind `spawn` next to it every `interval` seconds
+    Hive { interval: f32, spawn: BrickKindId },
+    // Breaking every key brick of a key unlocks the bricks locked with it
+    Key(u8),
+    Locked(u8),
+}
+
+// On-disk layout of a `.level.ron` file
+#[derive(Deserialize)]
+struct LevelFile {
+    name: String,
+    // Centre of the top-left cell
+    origin: (f32, f32),
+    // Distance between cell centres (column, row)
+    spacing: (f32, f32),
+    // Blast radius of the level's explosive bricks
+    #[serde(default)]
+    explosion_radius: Option<f32>,
+    // Seconds a regenerating brick must go unhurt to heal
+    #[serde(default)]
+    regeneration_delay: Option<f32>,
+    // Seconds between two bricks from a hive
+    #[serde(default)]
+    hive_interval: Option<f32>,
+    columns: usize,
+    rows: usize,
+    // One string per row, one symbol per cell
+    cells: Vec<String>,
+    // Groups of cells moving together
+    #[serde(default)]
+    formations: Vec<FormationFile>,
+    // Extra brick kinds by cell symbol, replacing a built-in kind with the same symbol.
+    // Sorted so the kinds get the same ids on every load.
+    #[serde(default)]
+    kinds: BTreeMap<char, BrickKindFile>,
+}
+
+// Brick kind declared in a `.level.ron` file
+#[derive(Deserialize)]
+struct BrickKindFile {
+    hit_points: u32,
+    score: u32,
+    // RGB colours from one hit point left up to full health
+    colors: Vec<(f32, f32, f32)>,
+}
+
+// Cells of a `.level.ron` file that move together along a patrol
+#[derive(Deserialize)]
+struct FormationFile {
+    rows: Vec<usize>,
+    // Every column of the rows when left out
+    #[serde(default)]
+    columns: Option<Vec<usize>>,
+    patrol: Patrol,
+}
+
+impl FormationFile {
+    fn contains(&self, row: usize, col: usize) -> bool {
+        self.rows.contains(&row)
+            && self
+                .columns
+                .as_ref()
+                .is_none_or(|columns| columns.contains(&col))
+    }
+}
+
+// On-disk layout of a `.campaign.ron` file
+#[derive(Deserialize)]
+struct CampaignFile {
+    // Level asset paths, played in order
+    levels: Vec<String>,
+}
+
+#[derive(Debug, Error)]
+pub enum CampaignLoaderError {
+    #[error("could not read campaign file: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("could not parse campaign file: {0}")]
+    Ron(#[from] ron::error::SpannedError),
+    #[error("campaign has no levels")]
+    Empty,
+}
+
+#[derive(Debug, Error)]
+pub enum LevelLoaderError {
+    #[error("could not read level file: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("could not parse level file: {0}")]
+    Ron(#[from] ron::error::SpannedError),
+    #[error("level declares {expected} rows but has {found}")]
+    RowCount { expected: usize, found: usize },
+    #[error("row {row} declares {expected} columns but has {found}")]
+    ColumnCount {
+        row: usize,
+        expected: usize,
+        found: usize,
+    },
+    #[error("unknown brick kind '{symbol}' at row {row}, column {col}")]
+    UnknownBrickKind {
+        symbol: char,
+        row: usize,
+        col: usize,
+    },
+    #[error("locked brick '{symbol}' at row {row}, column {col} has no matching key brick")]
+    MissingKey {
+        symbol: char,
+        row: usize,
+        col: usize,
+    },
+    #[error("brick kind '{symbol}' uses a symbol reserved for special bricks")]
+    ReservedKindSymbol { symbol: char },
+    #[error("brick kind '{symbol}' needs at least one hit point and one colour")]
+    InvalidBrickKind { symbol: char },
+    #[error("formation {formation} refers to a cell outside the grid")]
+    FormationOutsideGrid { formation: usize },
+    #[error("formation {formation} has a patrol with no time between moves")]
+    InvalidPatrolTiming { formation: usize },
+    #[error("{setting} must be a positive number of seconds")]
+    InvalidBrickTiming { setting: &'static str },
+    #[error("cell at row {row}, column {col} belongs to more than one formation")]
+    OverlappingFormations { row: usize, col: usize },
+    #[error("brick at row {row}, column {col} ({x}, {y}) is outside the arena")]
+    OutOfArena {
+        row: usize,
+        col: usize,
+        x: f32,
+        y: f32,
+    },
+}
+
+// Loads `.level.ron` files. Each symbol in `cells` is a brick: 'L' low, 'M' mid,
+// 'H' high and 'S' super, or a kind declared in `kinds`. 'X' is explosive, '#' steel,
+// 'R' regenerating and 'Q' a hive, '1'-'9' are keys and 'a'-'i' stay locked until the
+// key of the same rank breaks. '.' leaves the cell empty.
+#[derive(Default)]
+pub struct LevelLoader;
+
+impl AssetLoader for LevelLoader {
+    type Asset = Level;
+    type Settings = ();
+    type Error = LevelLoaderError;
+
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        _settings: &'a (),
+        _load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<Level, LevelLoaderError>> {
+        Box::pin(async move {
+            let mut bytes = Vec::new();
+            reader.read_to_end(&mut bytes).await?;
+            let file = ron::de::from_bytes::<LevelFile>(&bytes)?;
+            Level::from_file(file)
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["level.ron"]
+    }
+}
+
+impl Level {
+    fn from_file(file: LevelFile) -> Result<Level, LevelLoaderError> {
+        if file.cells.len() != file.rows {
+            return Err(LevelLoaderError::RowCount {
+                expected: file.rows,
+                found: file.cells.len(),
+            });
+        }
+        // Both end up in timers, which cannot run for a negative or NaN time
+        for (setting, seconds) in [
+            ("regeneration_delay", file.regeneration_delay),
+            ("hive_interval", file.hive_interval),
+        ] {
+            if seconds.is_some_and(|seconds| !seconds.is_finite() || seconds <= 0.0) {
+                return Err(LevelLoaderError::InvalidBrickTiming { setting });
+            }
+        }
+        for (formation, group) in file.formations.iter().enumerate() {
+            let rows_inside = group.rows.iter().all(|row| *row < file.rows);
+            let columns_inside = group
+                .columns
+                .iter()
+                .flatten()
+                .all(|col| *col < file.columns);
+            if !rows_inside || !columns_inside {
+                return Err(LevelLoaderError::FormationOutsideGrid { formation });
+            }
+            // Waves and marches divide by their timing
+            if matches!(
+                group.patrol,
+                Patrol::Wave { period, .. } | Patrol::Descend { interval: period, .. }
+                    if !period.is_finite() || period <= 0.0
+            ) {
+                return Err(LevelLoaderError::InvalidPatrolTiming { formation });
+            }
+        }
+        let mut kinds = BrickKinds::default();
+        for (&symbol, kind) in &file.kinds {
+            if Level::is_special_cell(symbol) {
+                return Err(LevelLoaderError::ReservedKindSymbol { symbol });
+            }
+            if kind.hit_points == 0 || kind.colors.is_empty() {
+                return Err(LevelLoaderError::InvalidBrickKind { symbol });
+            }
+            let colors = kind
+                .colors
+                .iter()
+                .map(|&(r, g, b)| Color::rgb(r, g, b))
+                .collect();
+            kinds.set(
+                symbol,
+                BrickKind {
+                    hit_points: kind.hit_points,
+                    score: kind.score,
+                    colors,
+                },
+            );
+        }
+        let grid = BrickGrid {
+            origin: Vec2::new(file.origin.0, file.origin.1),
+            spacing: Vec2::new(file.spacing.0, file.spacing.1),
+            columns: file.columns,
+            rows: file.rows,
+        };
+        let mut bricks = Vec::new();
+        // Locked bricks and where they are, checked against the keys once all are read
+        let mut locks = Vec::new();
+        for (row, cells) in file.cells.iter().enumerate() {
+            let symbols: Vec<char> = cells.chars().collect();
+            if symbols.len() != file.columns {
+                return Err(LevelLoaderError::ColumnCount {
+                    row,
+                    expected: file.columns,
+                    found: symbols.len(),
+                });
+            }
+            for (col, symbol) in symbols.into_iter().enumerate() {
+                if symbol == EMPTY_CELL {
+                    continue;
+                }
+                let (kind, role) = Level::parse_cell(symbol, &file, &kinds)
+                    .ok_or(LevelLoaderError::UnknownBrickKind { symbol, row, col })?;
+                if let Some(BrickRole::Locked(lock)) = role {
+                    locks.push((lock, symbol, row, col));
+                }
+                let mut formations = file
+                    .formations
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, group)| group.contains(row, col))
+                    .map(|(formation, _)| formation);
+                let formation = formations.next();
+                if formations.next().is_some() {
+                    return Err(LevelLoaderError::OverlappingFormations { row, col });
+                }
+                let cell = GridCell { row, col };
+                let Vec2 { x, y } = grid.position(cell);
+                // Moving bricks must stay inside all along their patrol
+                let (min, max) = formation.map_or((Vec2::ZERO, Vec2::ZERO), |formation| {
+                    file.formations[formation].patrol.reach()
+                });
+                if !Level::inside_arena(x + min.x, y + min.y)
+                    || !Level::inside_arena(x + max.x, y + max.y)
+                {
+                    return Err(LevelLoaderError::OutOfArena { row, col, x, y });
+                }
+                bricks.push(LevelBrick {
+                    kind,
+                    position: Vec3 { x, y, z: 0.0 },
+                    cell,
+                    role,
+                    formation,
+                });
+            }
+        }
+        // A locked brick without a key could never be broken
+        for (lock, symbol, row, col) in locks {
+            let has_key = bricks
+                .iter()
+                .any(|brick| matches!(brick.role, Some(BrickRole::Key(key)) if key == lock));
+            if !has_key {
+                return Err(LevelLoaderError::MissingKey { symbol, row, col });
+            }
+        }
+        Ok(Level {
+            name: file.name,
+            bricks,
+            grid,
+            kinds,
+            formations: file
+                .formations
+                .into_iter()
+                .map(|group| group.patrol)
+                .collect(),
+        })
+    }
+
+    // Kind and role of the brick a cell symbol stands for. Special bricks are built
+    // on the level's low, mid, high and super kinds.
+    fn parse_cell(
+        symbol: char,
+        file: &LevelFile,
+        kinds: &BrickKinds,
+    ) -> Option<(BrickKindId, Option<BrickRole>)> {
+        let rank = |first: char| symbol as u8 - first as u8;
+        let kind = |symbol: char| kinds.id(symbol);
+        match symbol {
+            EXPLOSIVE_CELL => Some((
+                kind(LOW_KIND)?,
+                Some(BrickRole::Explosive {
+                    radius: file.explosion_radius.unwrap_or(EXPLOSION_RADIUS),
+                }),
+            )),
+            STEEL_CELL => Some((kind(SUPER_KIND)?, Some(BrickRole::Indestructible))),
+            REGENERATING_CELL => Some((
+                kind(HIGH_KIND)?,
+                Some(BrickRole::Regenerating {
+                    delay: file.regeneration_delay.unwrap_or(REGENERATION_DELAY),
+                }),
+            )),
+            HIVE_CELL => Some((
+                kind(HIGH_KIND)?,
+                Some(BrickRole::Hive {
+                    interval: file.hive_interval.unwrap_or(HIVE_INTERVAL),
+                    spawn: kind(LOW_KIND)?,
+                }),
+            )),
+            _ if KEY_CELLS.contains(&symbol) => Some((
+                kind(LOW_KIND)?,
+                Some(BrickRole::Key(rank(*KEY_CELLS.start()))),
+            )),
+            _ if LOCKED_CELLS.contains(&symbol) => Some((
+                kind(MID_KIND)?,
+                Some(BrickRole::Locked(rank(*LOCKED_CELLS.start()))),
+            )),
+            _ => kind(symbol).map(|kind| (kind, None)),
+        }
+    }
+
+    // Symbols that mean something other than a brick kind
+    fn is_special_cell(symbol: char) -> bool {
+        matches!(
+            symbol,
+            EMPTY_CELL | EXPLOSIVE_CELL | STEEL_CELL | REGENERATING_CELL | HIVE_CELL
+        ) || KEY_CELLS.contains(&symbol)
+            || LOCKED_CELLS.contains(&symbol)
+    }
+
+    // Whole brick must fit between the walls
+    pub fn inside_arena(x: f32, y: f32) -> bool {
+        let left = wall::LEFT_WALL + wall::WALL_THICKNESS / 2.0;
+        let right = wall::RIGHT_WALL - wall::WALL_THICKNESS / 2.0;
+        let top = wall::TOP_WALL - wall::WALL_THICKNESS / 2.0;
+        let bottom = wall::BOTTOM_WALL;
+        x - BRICK_SIZE.x / 2.0 >= left
+            && x + BRICK_SIZE.x / 2.0 <= right
+            && y + BRICK_SIZE.y / 2.0 <= top
+            && y - BRICK_SIZE.y / 2.0 >= bottom
+    }
+}
+
+#[derive(Default)]
+pub struct CampaignLoader;
+
+impl AssetLoader for CampaignLoader {
+    type Asset = Campaign;
+    type Settings = ();
+    type Error = CampaignLoaderError;
+
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        _settings: &'a (),
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<Campaign, CampaignLoaderError>> {
+        Box::pin(async move {
+            let mut bytes = Vec::new();
+            reader.read_to_end(&mut bytes).await?;
+            let file = ron::de::from_bytes::<CampaignFile>(&bytes)?;
+            if file.levels.is_empty() {
+                return Err(CampaignLoaderError::Empty);
+            }
+            Ok(Campaign {
+                levels: file
+                    .levels
+                    .into_iter()
+                    .map(|path| load_context.load(path))
+                    .collect(),
+            })
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["campaign.ron"]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn load(source: &str) -> Result<Level, LevelLoaderError> {
+        Level::from_file(ron::de::from_str::<LevelFile>(source)?)
+    }
+
+    // Level of the given grid size laid out like the first shipped level
+    fn level(columns: usize, rows: usize, cells: &[&str]) -> String {
+        let cells: Vec<String> = cells.iter().map(|row| format!("{:?}", row)).collect();
+        format!(
+            "(name: \"Test\", origin: (-240.0, 300.0), spacing: (120.0, 60.0), \
+             columns: {}, rows: {}, cells: [{}])",
+            columns,
+            rows,
+            cells.join(", ")
+        )
+    }
+
+    #[test]
+    fn shipped_levels_load() {
+        let sources = [
+            include_str!("../assets/levels/01.level.ron"),
+            include_str!("../assets/levels/02.level.ron"),
+            include_str!("../assets/levels/03.level.ron"),
+            include_str!("../assets/levels/04.level.ron"),
+            include_str!("../assets/levels/05.level.ron"),
+            include_str!("../assets/levels/06.level.ron"),
+            include_str!("../assets/levels/07.level.ron"),
+        ];
+        for source in sources {
+            let level = load(source).unwrap();
+            assert!(!level.bricks.is_empty(), "{} has no bricks", level.name);
+        }
+    }
+
+    #[test]
+    fn empty_cells_are_skipped() {
+        let level = load(&level(3, 2, &["L.M", "..S"])).unwrap();
+        let cells: Vec<(usize, usize)> = level
+            .bricks
+            .iter()
+            .map(|brick| (brick.cell.row, brick.cell.col))
+            .collect();
+        assert_eq!(cells, [(0, 0), (0, 2), (1, 2)]);
+        assert_eq!(level.bricks[0].position, Vec3::new(-240.0, 300.0, 0.0));
+        assert_eq!(level.bricks[2].position, Vec3::new(0.0, 240.0, 0.0));
+    }
+
+    #[test]
+    fn wrong_row_count() {
+        let error = load(&level(2, 3, &["LL", "MM"])).unwrap_err();
+        assert!(matches!(
+            error,
+            LevelLoaderError::RowCount {
+                expected: 3,
+                found: 2
+            }
+        ));
+    }
+
+    #[test]
+    fn wrong_column_count() {
+        let error = load(&level(3, 2, &["LLL", "MM"])).unwrap_err();
+        assert!(matches!(
+            error,
+            LevelLoaderError::ColumnCount {
+                row: 1,
+                expected: 3,
+                found: 2
+            }
+        ));
+    }
+
+    #[test]
+    fn unknown_symbol() {
+        let error = load(&level(3, 2, &["LLL", "M?M"])).unwrap_err();
+        assert!(matches!(
+            error,
+            LevelLoaderError::UnknownBrickKind {
+                symbol: '?',
+                row: 1,
+                col: 1
+            }
+        ));
+    }
+
+    #[test]
+    fn cell_outside_arena() {
+        let error = load(&level(6, 1, &["LLLLLL"])).unwrap_err();
+        assert!(matches!(
+            error,
+            LevelLoaderError::OutOfArena { row: 0, col: 5, .. }
+        ));
+    }
+
+    #[test]
+    fn wave_needs_a_period() {
+        let source = level(2, 1, &["LL"]).replace(
+            "])",
+            "], formations: [(rows: [0], patrol: Wave(amplitude: (20.0, 0.0), period: 0.0))])",
+        );
+        let error = load(&source).unwrap_err();
+        assert!(matches!(
+            error,
+            LevelLoaderError::InvalidPatrolTiming { formation: 0 }
+        ));
+    }
+
+    #[test]
+    fn descend_needs_an_interval() {
+        let source = level(2, 1, &["LL"]).replace(
+            "])",
+            "], formations: [(rows: [0], patrol: Descend(step: 10.0, steps: 2, \
+             interval: -1.0, drop: 20.0, max_drop: 60.0))])",
+        );
+        let error = load(&source).unwrap_err();
+        assert!(matches!(
+            error,
+            LevelLoaderError::InvalidPatrolTiming { formation: 0 }
+        ));
+    }
+
+    #[test]
+    fn patrol_timing_must_be_a_number() {
+        let source = level(2, 1, &["LL"]).replace(
+            "])",
+            "], formations: [(rows: [0], patrol: Wave(amplitude: (20.0, 0.0), period: NaN))])",
+        );
+        let error = load(&source).unwrap_err();
+        assert!(matches!(
+            error,
+            LevelLoaderError::InvalidPatrolTiming { formation: 0 }
+        ));
+    }
+
+    #[test]
+    fn regeneration_delay_must_be_positive() {
+        let source = level(2, 1, &["RR"]).replace("])", "], regeneration_delay: Some(-2.0))");
+        let error = load(&source).unwrap_err();
+        assert!(matches!(
+            error,
+            LevelLoaderError::InvalidBrickTiming {
+                setting: "regeneration_delay"
+            }
+        ));
+    }
+
+    #[test]
+    fn hive_interval_must_be_a_number() {
+        let source = level(2, 1, &["QL"]).replace("])", "], hive_interval: Some(NaN))");
+        let error = load(&source).unwrap_err();
+        assert!(matches!(
+            error,
+            LevelLoaderError::InvalidBrickTiming {
+                setting: "hive_interval"
+            }
+        ));
+    }
+
+    #[test]
+    fn locked_brick_needs_its_key() {
+        let error = load(&level(2, 1, &["1b"])).unwrap_err();
+        assert!(matches!(
+            error,
+            LevelLoaderError::MissingKey {
+                symbol: 'b',
+                row: 0,
+                col: 1
+            }
+        ));
+    }
+}
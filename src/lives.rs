@@ -0,0 +1,88 @@
+use bevy::prelude::*;
+
+use crate::state::GameState;
+
+pub const STARTING_LIVES: u32 = 3;
+// Seconds between losing a ball and serving the next one
+const RESPAWN_DELAY: f32 = 1.0;
+
+#[derive(Resource)]
+pub struct Lives {
+    pub remaining: u32,
+    pub starting: u32,
+}
+
+impl Lives {
+    pub fn new(starting: u32) -> Lives {
+        Lives {
+            remaining: starting,
+            starting,
+        }
+    }
+}
+
+#[derive(Resource)]
+struct RespawnTimer(Timer);
+
+pub struct LivesPlugin {
+    // Lives at the start of every game
+    pub starting_lives: u32,
+}
+
+impl Default for LivesPlugin {
+    fn default() -> Self {
+        LivesPlugin {
+            starting_lives: STARTING_LIVES,
+        }
+    }
+}
+
+impl Plugin for LivesPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Lives::new(self.starting_lives))
+            .add_systems(OnEnter(GameState::MainMenu), LivesPlugin::reset_lives)
+            .add_systems(OnExit(GameState::EndMenu), LivesPlugin::reset_lives)
+            .add_systems(OnEnter(GameState::BallLost), LivesPlugin::lose_life)
+            .add_systems(OnExit(GameState::BallLost), LivesPlugin::remove_timer)
+            .add_systems(
+                Update,
+                LivesPlugin::tick_respawn.run_if(in_state(GameState::BallLost)),
+            );
+    }
+}
+
+impl LivesPlugin {
+    fn reset_lives(mut lives: ResMut<Lives>) {
+        lives.remaining = lives.starting;
+    }
+
+    // Game over once the last life is gone, otherwise wait and serve again
+    fn lose_life(
+        mut commands: Commands,
+        mut lives: ResMut<Lives>,
+        mut next_state: ResMut<NextState<GameState>>,
+    ) {
+        commands.insert_resource(RespawnTimer(Timer::from_seconds(
+            RESPAWN_DELAY,
+            TimerMode::Once,
+        )));
+        lives.remaining = lives.remaining.saturating_sub(1);
+        if lives.remaining == 0 {
+            next_state.set(GameState::EndMenu);
+        }
+    }
+
+    fn tick_respawn(
+        mut timer: ResMut<RespawnTimer>,
+        time: Res<Time>,
+        mut next_state: ResMut<NextState<GameState>>,
+    ) {
+        if timer.0.tick(time.delta()).just_finished() {
+            next_state.set(GameState::InGame);
+        }
+    }
+
+    fn remove_timer(mut commands: Commands) {
+        commands.remove_resource::<RespawnTimer>();
+    }
+}
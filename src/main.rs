@@ -2,26 +2,55 @@
 use bevy::prelude::*;
 
 mod ball;
+mod bindings;
 mod brick;
+mod broadphase;
 mod collider;
+mod combo;
 mod events;
+mod formation;
+mod highscore;
+mod interpolation;
+mod laser;
+mod level;
+mod lives;
 mod paddle;
+mod powerup;
+mod rng;
 mod schedule;
+mod settings;
 mod state;
+mod storage;
+mod stress;
 mod ui;
 mod wall;
 mod window;
 
 use ball::BallPlugin;
+use bindings::BindingsPlugin;
 use brick::BrickPlugin;
+use broadphase::BroadPhasePlugin;
 use collider::ColliderPlugin;
+use combo::ComboPlugin;
 use events::EventPlugin;
+use formation::FormationPlugin;
+use highscore::HighScorePlugin;
+use interpolation::InterpolationPlugin;
+use laser::LaserPlugin;
+use level::LevelPlugin;
+use lives::LivesPlugin;
 use paddle::PaddlePlugin;
+use powerup::PowerUpPlugin;
+use rng::RngPlugin;
 use schedule::SchedulePlugin;
+use settings::SettingsPlugin;
 use state::StatePlugin;
+use stress::StressPlugin;
 use ui::{
-    assetloader::AssetLoaderPlugin, endmenu::EndMenuPlugin, mainmenu::MainMenuPlugin,
-    pausemenu::PauseMenuPlugin, scoreboard::ScoreBoardPlugin,
+    assetloader::AssetLoaderPlugin, controlsmenu::ControlsMenuPlugin, endmenu::EndMenuPlugin,
+    fpscounter::FpsCounterPlugin, highscores::HighScoresPlugin, levelcleared::LevelClearedPlugin,
+    mainmenu::MainMenuPlugin, pausemenu::PauseMenuPlugin, scoreboard::ScoreBoardPlugin,
+    settingsmenu::SettingsMenuPlugin,
 };
 use wall::WallPlugin;
 use window::WindowPlugin;
@@ -32,16 +61,34 @@ fn main() {
         .add_plugins(EventPlugin)
         .add_plugins(WindowPlugin)
         .add_plugins(StatePlugin)
+        .add_plugins(LevelPlugin)
+        .add_plugins(LivesPlugin::default())
+        .add_plugins(HighScorePlugin)
+        .add_plugins(SettingsPlugin)
+        .add_plugins(BindingsPlugin)
         .add_plugins(AssetLoaderPlugin)
         .add_plugins(MainMenuPlugin)
         .add_plugins(PauseMenuPlugin)
         .add_plugins(EndMenuPlugin)
+        .add_plugins(HighScoresPlugin)
+        .add_plugins(SettingsMenuPlugin)
+        .add_plugins(ControlsMenuPlugin)
+        .add_plugins(FpsCounterPlugin)
+        .add_plugins(LevelClearedPlugin)
         .add_plugins(ScoreBoardPlugin)
-        .add_plugins(SchedulePlugin)
+        .add_plugins(SchedulePlugin::default())
+        .add_plugins(RngPlugin::default())
+        .add_plugins(InterpolationPlugin)
         .add_plugins(BrickPlugin)
+        .add_plugins(FormationPlugin)
         .add_plugins(BallPlugin)
         .add_plugins(WallPlugin)
         .add_plugins(PaddlePlugin)
         .add_plugins(ColliderPlugin)
+        .add_plugins(BroadPhasePlugin)
+        .add_plugins(ComboPlugin)
+        .add_plugins(PowerUpPlugin)
+        .add_plugins(LaserPlugin)
+        .add_plugins(StressPlugin)
         .run();
 }
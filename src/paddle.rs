@@ -1,29 +1,79 @@
+use crate::ball::{AttachedToPaddle, Ball};
+use crate::bindings::{Action, Actions};
 use crate::collider::Collider;
+use crate::events::{CollisionEvent, CollisionKind};
+use crate::interpolation::Interpolated;
+use crate::powerup::ActiveEffects;
 use crate::schedule::InGameSet;
+use crate::settings::Settings;
 use crate::state::GameState;
 use crate::wall;
 use bevy::prelude::*;
 
-const PADDLE_SIZE: Vec3 = Vec3::new(120.0, 20.0, 0.0);
-const PADDLE_Y_OFFSET: f32 = -340.0;
+pub const PADDLE_SIZE: Vec3 = Vec3::new(120.0, 20.0, 0.0);
+pub const PADDLE_Y_OFFSET: f32 = -340.0;
 const PADDLE_SPEED: f32 = 360.0;
 const PADDLE_PADDING: f32 = 10.0;
 const PADDLE_COLOR: Color = Color::rgb(0.3, 0.3, 0.7);
+// Rebound parameters
+const MAX_REBOUND_ANGLE: f32 = 5.0 * std::f32::consts::PI / 12.0;
+const PADDLE_VELOCITY_CARRY: f32 = 0.3;
+
+// How the ball leaves the top of the paddle
+#[derive(Resource)]
+pub struct PaddleRebound {
+    // Largest angle from vertical, reached at the ends of the paddle
+    pub max_angle: f32,
+    // Fraction of the paddle velocity added to the ball
+    pub velocity_carry: f32,
+}
+
+impl Default for PaddleRebound {
+    fn default() -> Self {
+        PaddleRebound {
+            max_angle: MAX_REBOUND_ANGLE,
+            velocity_carry: PADDLE_VELOCITY_CARRY,
+        }
+    }
+}
+
+impl PaddleRebound {
+    // Steer by the hit offset from the paddle centre, keeping the ball speed
+    pub fn rebound(&self, speed: f32, offset: f32, half_width: f32, paddle_velocity: f32) -> Vec2 {
+        let angle = (offset / half_width).clamp(-1.0, 1.0) * self.max_angle;
+        let steered = Vec2::new(angle.sin(), angle.cos()) * speed
+            + Vec2::new(paddle_velocity * self.velocity_carry, 0.0);
+        let angle = steered
+            .x
+            .atan2(steered.y)
+            .clamp(-self.max_angle, self.max_angle);
+        Vec2::new(angle.sin(), angle.cos()) * speed
+    }
+}
 
 pub struct PaddlePlugin;
 
 impl Plugin for PaddlePlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(OnEnter(GameState::PrepGame), PaddlePlugin::spawn_paddle)
+        app.init_resource::<PaddleRebound>()
+            .add_systems(OnEnter(GameState::PrepGame), PaddlePlugin::spawn_paddle)
             .add_systems(
                 Update,
                 PaddlePlugin::handle_input.in_set(InGameSet::UserInput),
             )
             .add_systems(
-                Update,
+                FixedUpdate,
+                PaddlePlugin::steer_ball.in_set(InGameSet::CollisionResponse),
+            )
+            .add_systems(
+                FixedUpdate,
                 PaddlePlugin::update_paddle.in_set(InGameSet::EntityUpdates),
             )
             .add_systems(OnExit(GameState::EndMenu), PaddlePlugin::despawn_paddle)
+            .add_systems(
+                OnExit(GameState::LevelCleared),
+                PaddlePlugin::despawn_paddle,
+            )
             .add_systems(OnEnter(GameState::MainMenu), PaddlePlugin::despawn_paddle);
     }
 }
@@ -39,30 +89,70 @@ impl PaddlePlugin {
         }
     }
 
-    fn handle_input(keyboard_input: Res<ButtonInput<KeyCode>>, mut query: Query<&mut Paddle>) {
+    fn handle_input(actions: Actions, settings: Res<Settings>, mut query: Query<&mut Paddle>) {
         for mut paddle in &mut query {
-            if keyboard_input.pressed(KeyCode::ArrowLeft) {
+            paddle.speed = PADDLE_SPEED * settings.paddle_sensitivity;
+            if actions.pressed(Action::MoveLeft) {
                 paddle.direction = -1.0;
             }
-            if keyboard_input.pressed(KeyCode::ArrowRight) {
+            if actions.pressed(Action::MoveRight) {
                 paddle.direction = 1.0;
             }
-            if !keyboard_input.any_pressed([KeyCode::ArrowLeft, KeyCode::ArrowRight]) {
+            if !actions.pressed(Action::MoveLeft) && !actions.pressed(Action::MoveRight) {
                 paddle.direction = 0.0;
             }
         }
     }
 
-    fn update_paddle(mut query: Query<(&mut Transform, &Paddle)>, time: Res<Time>) {
-        for (mut transform, paddle) in &mut query {
+    // Balls leaving the top face are steered, or caught while the paddle is sticky.
+    // Sides keep the plain reflection.
+    fn steer_ball(
+        mut commands: Commands,
+        mut collision_events: EventReader<CollisionEvent>,
+        mut ball_query: Query<&mut Ball>,
+        paddle_query: Query<(&Paddle, &Transform, &Collider)>,
+        paddle_rebound: Res<PaddleRebound>,
+        active_effects: Res<ActiveEffects>,
+    ) {
+        for event in collision_events.read() {
+            if event.kind != CollisionKind::Paddle || event.normal.y <= 0.0 {
+                continue;
+            }
+            let (Ok(mut ball), Ok((paddle, paddle_transform, paddle_collider))) = (
+                ball_query.get_mut(event.ball),
+                paddle_query.get(event.other),
+            ) else {
+                continue;
+            };
+            let (min, max) = paddle_collider.bounds(paddle_transform);
+            let offset = event.point.x - paddle_transform.translation.x;
+            let half_width = (max.x - min.x) / 2.0;
+            if active_effects.is_sticky() {
+                ball.velocity = Vec2::ZERO;
+                commands
+                    .entity(event.ball)
+                    .insert(AttachedToPaddle::caught(offset / half_width));
+                continue;
+            }
+            ball.velocity = paddle_rebound.rebound(
+                ball.velocity.length(),
+                offset,
+                half_width,
+                paddle.velocity(),
+            );
+        }
+    }
+
+    pub fn update_paddle(mut query: Query<(&mut Transform, &Paddle, &Collider)>, time: Res<Time>) {
+        for (mut transform, paddle, collider) in &mut query {
             let new_paddle_position =
-                transform.translation.x + paddle.direction * PADDLE_SPEED * time.delta_seconds();
+                transform.translation.x + paddle.velocity() * time.delta_seconds();
+            let (min, max) = collider.bounds(&transform);
+            let half_width = (max.x - min.x) / 2.0;
             let left_bound =
-                wall::LEFT_WALL + wall::WALL_THICKNESS / 2.0 + PADDLE_SIZE.x / 2.0 + PADDLE_PADDING;
-            let right_bound = wall::RIGHT_WALL
-                - wall::WALL_THICKNESS / 2.0
-                - PADDLE_SIZE.x / 2.0
-                - PADDLE_PADDING;
+                wall::LEFT_WALL + wall::WALL_THICKNESS / 2.0 + half_width + PADDLE_PADDING;
+            let right_bound =
+                wall::RIGHT_WALL - wall::WALL_THICKNESS / 2.0 - half_width - PADDLE_PADDING;
             transform.translation.x = new_paddle_position.clamp(left_bound, right_bound);
         }
     }
@@ -71,6 +161,14 @@ impl PaddlePlugin {
 #[derive(Component)]
 pub struct Paddle {
     direction: f32,
+    // Scaled by the paddle sensitivity setting
+    speed: f32,
+}
+
+impl Paddle {
+    pub fn velocity(&self) -> f32 {
+        self.direction * self.speed
+    }
 }
 
 #[derive(Bundle)]
@@ -78,19 +176,24 @@ pub struct PaddleBundle {
     paddle: Paddle,
     sprite: SpriteBundle,
     collider: Collider,
+    interpolated: Interpolated,
 }
 
 impl PaddleBundle {
     pub fn new() -> PaddleBundle {
+        let translation = Vec3 {
+            x: 0.0,
+            y: PADDLE_Y_OFFSET,
+            z: 0.0,
+        };
         PaddleBundle {
-            paddle: Paddle { direction: 0.0 },
+            paddle: Paddle {
+                direction: 0.0,
+                speed: PADDLE_SPEED,
+            },
             sprite: SpriteBundle {
                 transform: Transform {
-                    translation: Vec3 {
-                        x: 0.0,
-                        y: PADDLE_Y_OFFSET,
-                        z: 0.0,
-                    },
+                    translation,
                     scale: PADDLE_SIZE,
                     ..default()
                 },
@@ -100,7 +203,8 @@ impl PaddleBundle {
                 },
                 ..default()
             },
-            collider: Collider,
+            collider: Collider::aabb(PADDLE_SIZE),
+            interpolated: Interpolated::new(translation),
         }
     }
 }
@@ -0,0 +1,376 @@
+use bevy::prelude::*;
+use bevy::sprite::{MaterialMesh2dBundle, Mesh2dHandle};
+
+use crate::ball::{AttachedToPaddle, Ball, BALL_SPEED};
+use crate::brick::BrickPlugin;
+use crate::collider::Collider;
+use crate::events::{BrickDestroyedEvent, GrantBarrierEvent, MultiBallEvent};
+use crate::interpolation::Interpolated;
+use crate::lives::Lives;
+use crate::paddle::{Paddle, PADDLE_SIZE};
+use crate::rng::GameRng;
+use crate::schedule::InGameSet;
+use crate::settings::Settings;
+use crate::state::GameState;
+use crate::wall;
+
+// Falling capsule
+const CAPSULE_HALF_LENGTH: f32 = 14.0;
+const CAPSULE_RADIUS: f32 = 8.0;
+const CAPSULE_FALL_SPEED: f32 = 150.0;
+// Drawn above bricks, below balls
+const CAPSULE_Z: f32 = 0.5;
+// Seconds a timed effect lasts
+const EFFECT_DURATION: f32 = 10.0;
+// Factor applied by each stacked resize or speed effect
+const PADDLE_WIDTH_STEP: f32 = 1.5;
+const BALL_SPEED_STEP: f32 = 1.3;
+// Limits of stacked effects
+const MIN_PADDLE_WIDTH: f32 = 0.5;
+const MAX_PADDLE_WIDTH: f32 = 2.25;
+const MIN_BALL_SPEED: f32 = 0.6;
+const MAX_BALL_SPEED: f32 = 1.7;
+const MAX_SCORE_MULTIPLIER: u32 = 8;
+// Safety barrier lasts until it has taken this many bounces or this many seconds passed
+const BARRIER_BOUNCES: u32 = 3;
+const BARRIER_SECONDS: f32 = 15.0;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PowerUpKind {
+    ExpandPaddle,
+    ShrinkPaddle,
+    SlowBall,
+    FastBall,
+    ExtraLife,
+    ScoreMultiplier,
+    MultiBall,
+    Laser,
+    Sticky,
+    Barrier,
+    Fireball,
+}
+
+impl PowerUpKind {
+    const ALL: [PowerUpKind; 11] = [
+        PowerUpKind::ExpandPaddle,
+        PowerUpKind::ShrinkPaddle,
+        PowerUpKind::SlowBall,
+        PowerUpKind::FastBall,
+        PowerUpKind::ExtraLife,
+        PowerUpKind::ScoreMultiplier,
+        PowerUpKind::MultiBall,
+        PowerUpKind::Laser,
+        PowerUpKind::Sticky,
+        PowerUpKind::Barrier,
+        PowerUpKind::Fireball,
+    ];
+
+    fn random(rng: &mut GameRng) -> PowerUpKind {
+        PowerUpKind::ALL[rng.0.usize(..PowerUpKind::ALL.len())]
+    }
+
+    fn color(&self) -> Color {
+        match self {
+            PowerUpKind::ExpandPaddle => Color::rgb(0.3, 0.5, 1.0),
+            PowerUpKind::ShrinkPaddle => Color::rgb(0.6, 0.2, 0.6),
+            PowerUpKind::SlowBall => Color::rgb(1.0, 0.6, 0.1),
+            PowerUpKind::FastBall => Color::rgb(1.0, 0.2, 0.2),
+            PowerUpKind::ExtraLife => Color::rgb(0.5, 0.5, 0.5),
+            PowerUpKind::ScoreMultiplier => Color::rgb(0.2, 0.8, 0.3),
+            PowerUpKind::MultiBall => Color::rgb(0.2, 0.8, 0.9),
+            PowerUpKind::Laser => Color::rgb(0.9, 0.1, 0.1),
+            PowerUpKind::Sticky => Color::rgb(0.9, 0.9, 0.2),
+            PowerUpKind::Barrier => Color::rgb(0.3, 0.9, 0.9),
+            PowerUpKind::Fireball => Color::rgb(1.0, 0.4, 0.0),
+        }
+    }
+}
+
+// Chance of a broken brick dropping a capsule, by the hit points it was spawned with
+#[derive(Resource)]
+pub struct PowerUpDrops {
+    // From one hit point up, the last chance also covers any tougher brick
+    pub chances: Vec<f32>,
+}
+
+impl Default for PowerUpDrops {
+    fn default() -> Self {
+        PowerUpDrops {
+            chances: vec![0.08, 0.12, 0.18, 0.3],
+        }
+    }
+}
+
+impl PowerUpDrops {
+    fn chance(&self, hit_points: u32) -> f32 {
+        let index = (hit_points as usize).saturating_sub(1);
+        self.chances
+            .get(index)
+            .or(self.chances.last())
+            .copied()
+            .unwrap_or(0.0)
+    }
+}
+
+struct ActiveEffect {
+    kind: PowerUpKind,
+    timer: Timer,
+}
+
+// Timed effects currently running, each pickup stacks on top of the others
+#[derive(Resource, Default)]
+pub struct ActiveEffects {
+    effects: Vec<ActiveEffect>,
+}
+
+impl ActiveEffects {
+    fn count(&self, kind: PowerUpKind) -> i32 {
+        self.effects
+            .iter()
+            .filter(|effect| effect.kind == kind)
+            .count() as i32
+    }
+
+    // Paddle width relative to its normal size
+    pub fn paddle_width(&self) -> f32 {
+        let steps = self.count(PowerUpKind::ExpandPaddle) - self.count(PowerUpKind::ShrinkPaddle);
+        PADDLE_WIDTH_STEP
+            .powi(steps)
+            .clamp(MIN_PADDLE_WIDTH, MAX_PADDLE_WIDTH)
+    }
+
+    // Ball speed relative to its normal speed
+    pub fn ball_speed(&self) -> f32 {
+        let steps = self.count(PowerUpKind::FastBall) - self.count(PowerUpKind::SlowBall);
+        BALL_SPEED_STEP
+            .powi(steps)
+            .clamp(MIN_BALL_SPEED, MAX_BALL_SPEED)
+    }
+
+    // Paddle cannons are armed, stacking only extends the time
+    pub fn has_laser(&self) -> bool {
+        self.count(PowerUpKind::Laser) > 0
+    }
+
+    // Paddle catches balls instead of bouncing them
+    pub fn is_sticky(&self) -> bool {
+        self.count(PowerUpKind::Sticky) > 0
+    }
+
+    // Balls burn straight through bricks
+    pub fn has_fireball(&self) -> bool {
+        self.count(PowerUpKind::Fireball) > 0
+    }
+
+    // Doubles with every stacked multiplier
+    pub fn score_multiplier(&self) -> u32 {
+        let steps = self.count(PowerUpKind::ScoreMultiplier) as u32;
+        2u32.saturating_pow(steps).min(MAX_SCORE_MULTIPLIER)
+    }
+}
+
+#[derive(Component)]
+pub struct PowerUp {
+    pub kind: PowerUpKind,
+}
+
+#[derive(Bundle)]
+struct PowerUpBundle {
+    mesh: MaterialMesh2dBundle<ColorMaterial>,
+    power_up: PowerUp,
+    collider: Collider,
+    interpolated: Interpolated,
+}
+
+impl PowerUpBundle {
+    fn new(
+        meshes: &mut Assets<Mesh>,
+        materials: &mut Assets<ColorMaterial>,
+        kind: PowerUpKind,
+        position: Vec3,
+    ) -> PowerUpBundle {
+        // The mesh is built upright, lay it down along the collider's x axis
+        let mesh = Mesh::from(Capsule2d::new(CAPSULE_RADIUS, CAPSULE_HALF_LENGTH * 2.0))
+            .rotated_by(Quat::from_rotation_z(std::f32::consts::FRAC_PI_2));
+        let translation = position.truncate().extend(CAPSULE_Z);
+        PowerUpBundle {
+            mesh: MaterialMesh2dBundle {
+                mesh: Mesh2dHandle(meshes.add(mesh)),
+                material: materials.add(ColorMaterial {
+                    color: kind.color(),
+                    ..Default::default()
+                }),
+                transform: Transform::from_translation(translation),
+                ..default()
+            },
+            power_up: PowerUp { kind },
+            collider: Collider::Capsule {
+                half_length: CAPSULE_HALF_LENGTH,
+                radius: CAPSULE_RADIUS,
+            },
+            interpolated: Interpolated::new(translation),
+        }
+    }
+}
+
+pub struct PowerUpPlugin;
+
+impl Plugin for PowerUpPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PowerUpDrops>()
+            .init_resource::<ActiveEffects>()
+            .add_systems(
+                FixedUpdate,
+                (
+                    PowerUpPlugin::fall,
+                    PowerUpPlugin::tick_effects,
+                    PowerUpPlugin::apply_paddle_width,
+                    PowerUpPlugin::apply_ball_speed,
+                )
+                    .chain()
+                    .in_set(InGameSet::EntityUpdates),
+            )
+            .add_systems(
+                FixedUpdate,
+                PowerUpPlugin::collect.in_set(InGameSet::CollisionResponse),
+            )
+            .add_systems(
+                FixedUpdate,
+                (
+                    PowerUpPlugin::drop_power_ups.after(BrickPlugin::despawn_brick),
+                    PowerUpPlugin::despawn_missed,
+                )
+                    .in_set(InGameSet::DespawnEntities),
+            )
+            // Effects end with the run, capsules also go with the arena
+            .add_systems(
+                OnEnter(GameState::MainMenu),
+                (PowerUpPlugin::clear_effects, PowerUpPlugin::despawn_all),
+            )
+            .add_systems(
+                OnExit(GameState::EndMenu),
+                (PowerUpPlugin::clear_effects, PowerUpPlugin::despawn_all),
+            )
+            .add_systems(OnExit(GameState::LevelCleared), PowerUpPlugin::despawn_all);
+    }
+}
+
+impl PowerUpPlugin {
+    // Roll a drop for every broken brick
+    fn drop_power_ups(
+        mut commands: Commands,
+        mut meshes: ResMut<Assets<Mesh>>,
+        mut materials: ResMut<Assets<ColorMaterial>>,
+        mut destroyed_events: EventReader<BrickDestroyedEvent>,
+        drops: Res<PowerUpDrops>,
+        mut rng: ResMut<GameRng>,
+    ) {
+        for event in destroyed_events.read() {
+            if rng.0.f32() < drops.chance(event.hit_points) {
+                commands.spawn(PowerUpBundle::new(
+                    &mut meshes,
+                    &mut materials,
+                    PowerUpKind::random(&mut rng),
+                    event.position,
+                ));
+            }
+        }
+    }
+
+    fn fall(mut query: Query<&mut Transform, With<PowerUp>>, time: Res<Time>) {
+        for mut transform in &mut query {
+            transform.translation.y -= CAPSULE_FALL_SPEED * time.delta_seconds();
+        }
+    }
+
+    // Capsules caught by the paddle start their effect
+    pub fn collect(
+        mut commands: Commands,
+        power_up_query: Query<(Entity, &PowerUp, &Transform, &Collider)>,
+        paddle_query: Query<(&Transform, &Collider), With<Paddle>>,
+        mut active_effects: ResMut<ActiveEffects>,
+        mut lives: ResMut<Lives>,
+        mut multi_ball_events: EventWriter<MultiBallEvent>,
+        mut barrier_events: EventWriter<GrantBarrierEvent>,
+    ) {
+        let Ok((paddle_transform, paddle_collider)) = paddle_query.get_single() else {
+            return;
+        };
+        for (entity, power_up, transform, collider) in &power_up_query {
+            if !collider.overlaps(transform, paddle_collider, paddle_transform) {
+                continue;
+            }
+            commands.entity(entity).despawn();
+            match power_up.kind {
+                // Instant, nothing to time
+                PowerUpKind::ExtraLife => lives.remaining += 1,
+                PowerUpKind::MultiBall => {
+                    multi_ball_events.send(MultiBallEvent);
+                }
+                PowerUpKind::Barrier => {
+                    barrier_events.send(GrantBarrierEvent {
+                        bounces: BARRIER_BOUNCES,
+                        seconds: BARRIER_SECONDS,
+                    });
+                }
+                kind => active_effects.effects.push(ActiveEffect {
+                    kind,
+                    timer: Timer::from_seconds(EFFECT_DURATION, TimerMode::Once),
+                }),
+            }
+        }
+    }
+
+    // Effects only run down while the game is being played
+    fn tick_effects(mut active_effects: ResMut<ActiveEffects>, time: Res<Time>) {
+        for effect in &mut active_effects.effects {
+            effect.timer.tick(time.delta());
+        }
+        active_effects
+            .effects
+            .retain(|effect| !effect.timer.finished());
+    }
+
+    fn apply_paddle_width(
+        mut query: Query<(&mut Transform, &mut Collider), With<Paddle>>,
+        active_effects: Res<ActiveEffects>,
+    ) {
+        let size = PADDLE_SIZE * Vec3::new(active_effects.paddle_width(), 1.0, 1.0);
+        for (mut transform, mut collider) in &mut query {
+            // Only touch the paddle when the size changes, the broadphase follows changes
+            if transform.scale != size {
+                transform.scale = size;
+                *collider = Collider::aabb(size);
+            }
+        }
+    }
+
+    fn apply_ball_speed(
+        mut query: Query<&mut Ball, Without<AttachedToPaddle>>,
+        active_effects: Res<ActiveEffects>,
+        settings: Res<Settings>,
+    ) {
+        let speed = BALL_SPEED * settings.difficulty.ball_speed() * active_effects.ball_speed();
+        for mut ball in &mut query {
+            ball.velocity = ball.velocity.normalize_or_zero() * speed;
+        }
+    }
+
+    fn despawn_missed(mut commands: Commands, query: Query<(Entity, &Transform), With<PowerUp>>) {
+        for (entity, transform) in &query {
+            if transform.translation.y < wall::BOTTOM_WALL {
+                commands.entity(entity).despawn();
+            }
+        }
+    }
+
+    fn clear_effects(mut active_effects: ResMut<ActiveEffects>) {
+        active_effects.effects.clear();
+    }
+
+    fn despawn_all(mut commands: Commands, query: Query<Entity, With<PowerUp>>) {
+        for entity in &query {
+            commands.entity(entity).despawn();
+        }
+    }
+}
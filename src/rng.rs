@@ -0,0 +1,33 @@
+use bevy::prelude::*;
+
+use crate::state::GameState;
+
+// Source of every random roll in gameplay, so a seed replays the same drops and spawns
+#[derive(Resource)]
+pub struct GameRng(pub fastrand::Rng);
+
+// Seed the game random numbers start from on every level
+#[derive(Resource)]
+struct RngSeed(Option<u64>);
+
+#[derive(Default)]
+pub struct RngPlugin {
+    // Fixed seed for every level, a fresh one is drawn each level when unset
+    pub seed: Option<u64>,
+}
+
+impl Plugin for RngPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(GameRng(fastrand::Rng::new()))
+            .insert_resource(RngSeed(self.seed))
+            .add_systems(OnEnter(GameState::PrepGame), RngPlugin::reseed);
+    }
+}
+
+impl RngPlugin {
+    fn reseed(mut rng: ResMut<GameRng>, seed: Res<RngSeed>) {
+        let seed = seed.0.unwrap_or_else(|| fastrand::u64(..));
+        info!("Game seed {}", seed);
+        rng.0.seed(seed);
+    }
+}
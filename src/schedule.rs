@@ -1,26 +1,47 @@
 use crate::state::GameState;
 use bevy::prelude::*;
 
+// Default gameplay ticks per second
+pub const TICK_RATE_HZ: f64 = 120.0;
+
 #[derive(SystemSet, Debug, Hash, PartialEq, Eq, Clone)]
 pub enum InGameSet {
     CheckGameStatus,
     UserInput,
     CollisionDetection,
+    CollisionResponse,
     EntityUpdates,
     DespawnEntities,
 }
 
-pub struct SchedulePlugin;
+pub struct SchedulePlugin {
+    // Gameplay ticks per second, independent of the frame rate
+    pub tick_rate_hz: f64,
+}
+
+impl Default for SchedulePlugin {
+    fn default() -> Self {
+        SchedulePlugin {
+            tick_rate_hz: TICK_RATE_HZ,
+        }
+    }
+}
 
 impl Plugin for SchedulePlugin {
     fn build(&self, app: &mut App) {
+        app.insert_resource(Time::<Fixed>::from_hz(self.tick_rate_hz));
+        // Input is read every frame so no key press falls between two ticks
         app.configure_sets(
             Update,
+            InGameSet::UserInput.run_if(in_state(GameState::InGame)),
+        );
+        app.configure_sets(
+            FixedUpdate,
             (
                 InGameSet::CheckGameStatus,
-                InGameSet::UserInput,
                 InGameSet::EntityUpdates,
                 InGameSet::CollisionDetection,
+                InGameSet::CollisionResponse,
                 InGameSet::DespawnEntities,
             )
                 .chain()
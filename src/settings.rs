@@ -0,0 +1,330 @@
+use bevy::audio::Volume;
+use bevy::prelude::*;
+use bevy::window::WindowMode;
+use serde::{Deserialize, Serialize};
+
+use crate::state::GameState;
+use crate::storage::{self, StorageError};
+
+const SETTINGS_FILE: &str = "settings.ron";
+const VOLUME_STEP: f32 = 0.1;
+const SENSITIVITY_STEP: f32 = 0.1;
+const MIN_SENSITIVITY: f32 = 0.5;
+const MAX_SENSITIVITY: f32 = 2.0;
+
+// How fast the ball goes for the player's chosen challenge
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+}
+
+impl Difficulty {
+    const ALL: [Difficulty; 3] = [Difficulty::Easy, Difficulty::Normal, Difficulty::Hard];
+
+    // Multiplier on the ball speed
+    pub fn ball_speed(self) -> f32 {
+        match self {
+            Difficulty::Easy => 0.8,
+            Difficulty::Normal => 1.0,
+            Difficulty::Hard => 1.25,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Difficulty::Easy => "Easy",
+            Difficulty::Normal => "Normal",
+            Difficulty::Hard => "Hard",
+        }
+    }
+}
+
+// Player preferences, kept in the platform config directory. Fields missing from the
+// file keep their defaults so older files still load.
+#[derive(Resource, Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct Settings {
+    // Volumes from 0 to 1, music and effects are scaled by the master volume
+    pub master_volume: f32,
+    pub music_volume: f32,
+    pub effects_volume: f32,
+    pub difficulty: Difficulty,
+    // Multiplier on the paddle speed
+    pub paddle_sensitivity: f32,
+    pub fullscreen: bool,
+    pub show_fps: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            master_volume: 1.0,
+            music_volume: 0.8,
+            effects_volume: 0.8,
+            difficulty: Difficulty::Normal,
+            paddle_sensitivity: 1.0,
+            fullscreen: false,
+            show_fps: false,
+        }
+    }
+}
+
+// One line of the settings screen
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Setting {
+    MasterVolume,
+    MusicVolume,
+    EffectsVolume,
+    Difficulty,
+    PaddleSensitivity,
+    Fullscreen,
+    ShowFps,
+}
+
+impl Setting {
+    pub const ALL: [Setting; 7] = [
+        Setting::MasterVolume,
+        Setting::MusicVolume,
+        Setting::EffectsVolume,
+        Setting::Difficulty,
+        Setting::PaddleSensitivity,
+        Setting::Fullscreen,
+        Setting::ShowFps,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Setting::MasterVolume => "Master Volume",
+            Setting::MusicVolume => "Music Volume",
+            Setting::EffectsVolume => "Effects Volume",
+            Setting::Difficulty => "Difficulty",
+            Setting::PaddleSensitivity => "Paddle Speed",
+            Setting::Fullscreen => "Fullscreen",
+            Setting::ShowFps => "Show FPS",
+        }
+    }
+}
+
+impl Settings {
+    fn load() -> Result<Settings, StorageError> {
+        let mut settings: Settings = storage::load(&storage::config_file(SETTINGS_FILE)?)?;
+        // Values edited by hand could be out of range
+        settings.master_volume = settings.master_volume.clamp(0.0, 1.0);
+        settings.music_volume = settings.music_volume.clamp(0.0, 1.0);
+        settings.effects_volume = settings.effects_volume.clamp(0.0, 1.0);
+        settings.paddle_sensitivity = settings
+            .paddle_sensitivity
+            .clamp(MIN_SENSITIVITY, MAX_SENSITIVITY);
+        Ok(settings)
+    }
+
+    fn save(&self) -> Result<(), StorageError> {
+        storage::save(&storage::config_file(SETTINGS_FILE)?, self)
+    }
+
+    // Move a setting `steps` notches up or down, toggles flip on any step
+    pub fn adjust(&mut self, setting: Setting, steps: i32) {
+        // Round to whole notches so repeated steps do not drift. Dividing by the notches
+        // per unit gives the nearest float to a notch, 0.9 rather than 9.0 * 0.1.
+        let step = |value: f32, size: f32, min: f32, max: f32| {
+            (((value + size * steps as f32) / size).round() / size.recip()).clamp(min, max)
+        };
+        match setting {
+            Setting::MasterVolume => {
+                self.master_volume = step(self.master_volume, VOLUME_STEP, 0.0, 1.0);
+            }
+            Setting::MusicVolume => {
+                self.music_volume = step(self.music_volume, VOLUME_STEP, 0.0, 1.0);
+            }
+            Setting::EffectsVolume => {
+                self.effects_volume = step(self.effects_volume, VOLUME_STEP, 0.0, 1.0);
+            }
+            Setting::Difficulty => {
+                let count = Difficulty::ALL.len() as i32;
+                let index = Difficulty::ALL
+                    .iter()
+                    .position(|difficulty| *difficulty == self.difficulty)
+                    .unwrap_or(0) as i32;
+                self.difficulty = Difficulty::ALL[(index + steps).rem_euclid(count) as usize];
+            }
+            Setting::PaddleSensitivity => {
+                self.paddle_sensitivity = step(
+                    self.paddle_sensitivity,
+                    SENSITIVITY_STEP,
+                    MIN_SENSITIVITY,
+                    MAX_SENSITIVITY,
+                );
+            }
+            Setting::Fullscreen => self.fullscreen = !self.fullscreen,
+            Setting::ShowFps => self.show_fps = !self.show_fps,
+        }
+    }
+
+    // Current value of a setting as shown on screen
+    pub fn describe(&self, setting: Setting) -> String {
+        let percent = |value: f32| format!("{}%", (value * 100.0).round());
+        let on_off = |value: bool| String::from(if value { "On" } else { "Off" });
+        match setting {
+            Setting::MasterVolume => percent(self.master_volume),
+            Setting::MusicVolume => percent(self.music_volume),
+            Setting::EffectsVolume => percent(self.effects_volume),
+            Setting::Difficulty => String::from(self.difficulty.name()),
+            Setting::PaddleSensitivity => format!("{:.1}x", self.paddle_sensitivity),
+            Setting::Fullscreen => on_off(self.fullscreen),
+            Setting::ShowFps => on_off(self.show_fps),
+        }
+    }
+}
+
+pub struct SettingsPlugin;
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Settings>()
+            .add_systems(Startup, SettingsPlugin::load_settings)
+            // Runs on the first frame too, while assets load and before the main menu
+            .add_systems(
+                Update,
+                (
+                    SettingsPlugin::apply_window_mode,
+                    SettingsPlugin::apply_volume,
+                )
+                    .run_if(resource_changed::<Settings>),
+            )
+            .add_systems(OnExit(GameState::Settings), SettingsPlugin::save_settings);
+    }
+}
+
+impl SettingsPlugin {
+    // A missing or broken file keeps the defaults
+    fn load_settings(mut settings: ResMut<Settings>) {
+        match Settings::load() {
+            Ok(loaded) => *settings = loaded,
+            Err(error) if error.is_missing() => (),
+            Err(error) => warn!("Using default settings: {}", error),
+        }
+    }
+
+    fn save_settings(settings: Res<Settings>) {
+        if let Err(error) = settings.save() {
+            error!("Could not save settings: {}", error);
+        }
+    }
+
+    fn apply_window_mode(settings: Res<Settings>, mut windows: Query<&mut Window>) {
+        let mode = if settings.fullscreen {
+            WindowMode::BorderlessFullscreen
+        } else {
+            WindowMode::Windowed
+        };
+        for mut window in &mut windows {
+            if window.mode != mode {
+                window.mode = mode;
+            }
+        }
+    }
+
+    fn apply_volume(settings: Res<Settings>, mut global_volume: ResMut<GlobalVolume>) {
+        global_volume.volume = Volume::new(settings.master_volume);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn volume_steps_land_on_notches() {
+        let mut settings = Settings {
+            master_volume: 0.0,
+            ..default()
+        };
+        for _ in 0..3 {
+            settings.adjust(Setting::MasterVolume, 1);
+        }
+        assert_eq!(settings.master_volume, 0.3);
+        assert_eq!(settings.describe(Setting::MasterVolume), "30%");
+        settings.adjust(Setting::MasterVolume, -1);
+        assert_eq!(settings.master_volume, 0.2);
+    }
+
+    #[test]
+    fn music_and_effects_steps_land_on_notches() {
+        let mut settings = Settings::default();
+        settings.adjust(Setting::MusicVolume, -3);
+        settings.adjust(Setting::EffectsVolume, 4);
+        assert_eq!(settings.music_volume, 0.5);
+        assert_eq!(settings.effects_volume, 1.0);
+        assert_eq!(settings.describe(Setting::MusicVolume), "50%");
+        assert_eq!(settings.describe(Setting::EffectsVolume), "100%");
+    }
+
+    #[test]
+    fn volumes_survive_a_save_and_load() {
+        let path = std::env::temp_dir()
+            .join(format!("settings-{}", std::process::id()))
+            .join(SETTINGS_FILE);
+        let settings = Settings {
+            master_volume: 0.6,
+            music_volume: 0.3,
+            effects_volume: 0.9,
+            ..default()
+        };
+        storage::save(&path, &settings).unwrap();
+        let loaded: Settings = storage::load(&path).unwrap();
+        let _ = std::fs::remove_dir_all(path.parent().unwrap());
+        assert_eq!(loaded, settings);
+    }
+
+    #[test]
+    fn older_file_gets_default_music_and_effects_volumes() {
+        let loaded: Settings = ron::de::from_str("(master_volume: 0.5)").unwrap();
+        assert_eq!(loaded.master_volume, 0.5);
+        assert_eq!(loaded.music_volume, 0.8);
+        assert_eq!(loaded.effects_volume, 0.8);
+    }
+
+    #[test]
+    fn value_between_notches_snaps_to_one() {
+        let mut settings = Settings {
+            master_volume: 0.37,
+            paddle_sensitivity: 1.04,
+            ..default()
+        };
+        settings.adjust(Setting::MasterVolume, 1);
+        settings.adjust(Setting::PaddleSensitivity, -1);
+        assert_eq!(settings.master_volume, 0.5);
+        assert_eq!(settings.paddle_sensitivity, 0.9);
+    }
+
+    #[test]
+    fn steps_stop_at_the_limits() {
+        let mut settings = Settings::default();
+        settings.adjust(Setting::MasterVolume, 5);
+        settings.adjust(Setting::PaddleSensitivity, -20);
+        assert_eq!(settings.master_volume, 1.0);
+        assert_eq!(settings.paddle_sensitivity, MIN_SENSITIVITY);
+        settings.adjust(Setting::PaddleSensitivity, 40);
+        assert_eq!(settings.paddle_sensitivity, MAX_SENSITIVITY);
+    }
+
+    #[test]
+    fn difficulty_wraps_around() {
+        let mut settings = Settings::default();
+        settings.adjust(Setting::Difficulty, 2);
+        assert_eq!(settings.difficulty, Difficulty::Easy);
+        settings.adjust(Setting::Difficulty, -1);
+        assert_eq!(settings.difficulty, Difficulty::Hard);
+    }
+
+    #[test]
+    fn toggles_flip_on_any_step() {
+        let mut settings = Settings::default();
+        settings.adjust(Setting::Fullscreen, -1);
+        settings.adjust(Setting::ShowFps, 3);
+        assert!(settings.fullscreen && settings.show_fps);
+    }
+}
@@ -1,4 +1,5 @@
 use crate::ball::Ball;
+use crate::bindings::{Action, Actions};
 use crate::paddle::Paddle;
 use bevy::prelude::*;
 
@@ -7,9 +8,14 @@ pub enum GameState {
     #[default]
     LoadAsset,
     MainMenu,
+    HighScores,
+    Settings,
+    Controls,
     PrepGame,
     InGame,
+    BallLost,
     Paused,
+    LevelCleared,
     EndMenu,
 }
 
@@ -30,9 +36,9 @@ impl StatePlugin {
     fn in_game_input_events(
         mut next_state: ResMut<NextState<GameState>>,
         state: Res<State<GameState>>,
-        keyboard_input: Res<ButtonInput<KeyCode>>,
+        actions: Actions,
     ) {
-        if keyboard_input.just_pressed(KeyCode::Escape) {
+        if actions.just_pressed(Action::Pause) {
             match state.get() {
                 GameState::InGame => next_state.set(GameState::Paused),
                 GameState::Paused => next_state.set(GameState::InGame),
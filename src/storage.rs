@@ -0,0 +1,78 @@
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+use directories::ProjectDirs;
+use ron::ser::PrettyConfig;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use thiserror::Error;
+
+const APPLICATION: &str = "bevy_helloworld";
+
+#[derive(Debug, Error)]
+pub enum StorageError {
+    #[error("no home directory on this platform")]
+    NoHomeDir,
+    #[error("could not access {path}: {source}")]
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[error("could not parse {path}: {source}")]
+    Parse {
+        path: PathBuf,
+        source: ron::error::SpannedError,
+    },
+    #[error("could not write {path}: {source}")]
+    Write { path: PathBuf, source: ron::Error },
+}
+
+impl StorageError {
+    // Nothing was saved yet
+    pub fn is_missing(&self) -> bool {
+        matches!(self, StorageError::Io { source, .. } if source.kind() == ErrorKind::NotFound)
+    }
+}
+
+// File in the platform data directory, for what the game keeps between runs
+pub fn data_file(name: &str) -> Result<PathBuf, StorageError> {
+    ProjectDirs::from("", "", APPLICATION)
+        .map(|dirs| dirs.data_dir().join(name))
+        .ok_or(StorageError::NoHomeDir)
+}
+
+// File in the platform config directory, for what the player chose
+pub fn config_file(name: &str) -> Result<PathBuf, StorageError> {
+    ProjectDirs::from("", "", APPLICATION)
+        .map(|dirs| dirs.config_dir().join(name))
+        .ok_or(StorageError::NoHomeDir)
+}
+
+pub fn load<T: DeserializeOwned>(path: &Path) -> Result<T, StorageError> {
+    let text = fs::read_to_string(path).map_err(|source| StorageError::Io {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    ron::de::from_str(&text).map_err(|source| StorageError::Parse {
+        path: path.to_path_buf(),
+        source,
+    })
+}
+
+pub fn save<T: Serialize>(path: &Path, value: &T) -> Result<(), StorageError> {
+    let text = ron::ser::to_string_pretty(value, PrettyConfig::default()).map_err(|source| {
+        StorageError::Write {
+            path: path.to_path_buf(),
+            source,
+        }
+    })?;
+    let io_error = |source| StorageError::Io {
+        path: path.to_path_buf(),
+        source,
+    };
+    if let Some(directory) = path.parent() {
+        fs::create_dir_all(directory).map_err(io_error)?;
+    }
+    fs::write(path, text).map_err(io_error)
+}
@@ -0,0 +1,80 @@
+use bevy::diagnostic::{FrameTimeDiagnosticsPlugin, LogDiagnosticsPlugin};
+use bevy::prelude::*;
+
+use crate::ball::{BallBundle, BALL_Z};
+use crate::brick::{BrickBundle, BrickKinds, LOW_KIND};
+use crate::state::GameState;
+
+// Run the game with this argument to fill the arena for profiling
+const STRESS_FLAG: &str = "--stress";
+// Tiny bricks packed below the level layout
+const STRESS_BRICK_SIZE: Vec3 = Vec3::new(10.0, 5.0, 1.0);
+const STRESS_BRICK_SPACING: Vec2 = Vec2::new(12.0, 7.0);
+const STRESS_COLUMNS: usize = 56;
+const STRESS_ROWS: usize = 42;
+const STRESS_ORIGIN: Vec2 = Vec2::new(-330.0, 90.0);
+// Balls fanned out upwards from above the paddle
+const STRESS_BALLS: usize = 48;
+const STRESS_BALL_Y: f32 = -250.0;
+const STRESS_BALL_SPEED: f32 = 400.0;
+
+pub struct StressPlugin;
+
+impl Plugin for StressPlugin {
+    fn build(&self, app: &mut App) {
+        if !std::env::args().any(|arg| arg == STRESS_FLAG) {
+            return;
+        }
+        // The FPS counter may have added it already
+        if !app.is_plugin_added::<FrameTimeDiagnosticsPlugin>() {
+            app.add_plugins(FrameTimeDiagnosticsPlugin);
+        }
+        app.add_plugins(LogDiagnosticsPlugin::default())
+            .add_systems(
+                OnEnter(GameState::PrepGame),
+                StressPlugin::spawn_stress_scene,
+            );
+    }
+}
+
+impl StressPlugin {
+    fn spawn_stress_scene(
+        mut commands: Commands,
+        mut meshes: ResMut<Assets<Mesh>>,
+        mut materials: ResMut<Assets<ColorMaterial>>,
+    ) {
+        let kinds = BrickKinds::default();
+        let Some(kind) = kinds.id(LOW_KIND) else {
+            return;
+        };
+        for row in 0..STRESS_ROWS {
+            for col in 0..STRESS_COLUMNS {
+                let position = Vec3 {
+                    x: STRESS_ORIGIN.x + STRESS_BRICK_SPACING.x * col as f32,
+                    y: STRESS_ORIGIN.y - STRESS_BRICK_SPACING.y * row as f32,
+                    z: 0.0,
+                };
+                commands
+                    .spawn(BrickBundle::new(kind, &kinds, position).with_size(STRESS_BRICK_SIZE));
+            }
+        }
+        for index in 0..STRESS_BALLS {
+            let spread = index as f32 / (STRESS_BALLS - 1) as f32 - 0.5;
+            let angle = spread * std::f32::consts::FRAC_PI_2;
+            let position = Vec3 {
+                x: spread * 600.0,
+                y: STRESS_BALL_Y,
+                z: BALL_Z,
+            };
+            commands.spawn(
+                BallBundle::new(&mut meshes, &mut materials, position)
+                    .with_velocity(Vec2::new(angle.sin(), angle.cos()) * STRESS_BALL_SPEED),
+            );
+        }
+        info!(
+            "Stress scene: {} bricks, {} balls",
+            STRESS_ROWS * STRESS_COLUMNS,
+            STRESS_BALLS
+        );
+    }
+}
@@ -1,7 +1,14 @@
-use bevy::prelude::*;
+use bevy::{
+    app::AppExit,
+    asset::{LoadState, RecursiveDependencyLoadState},
+    prelude::*,
+};
 
+use crate::level::CampaignHandle;
 use crate::state::GameState;
 
+const CAMPAIGN_PATH: &str = "main.campaign.ron";
+
 #[derive(Resource)]
 pub struct UiFont(pub Handle<Font>);
 
@@ -10,14 +17,32 @@ pub struct AssetLoaderPlugin;
 impl Plugin for AssetLoaderPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(OnEnter(GameState::LoadAsset), asset_load);
+        app.add_systems(
+            Update,
+            check_asset_loaded.run_if(in_state(GameState::LoadAsset)),
+        );
     }
 }
 
-fn asset_load(
-    mut commands: Commands,
+fn asset_load(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(UiFont(asset_server.load("fonts/FiraSans-Bold.ttf")));
+    commands.insert_resource(CampaignHandle(asset_server.load(CAMPAIGN_PATH)));
+}
+
+// Wait for the campaign and its levels before showing the main menu
+fn check_asset_loaded(
     asset_server: Res<AssetServer>,
+    campaign_handle: Res<CampaignHandle>,
     mut next_state: ResMut<NextState<GameState>>,
+    mut app_exit_writer: EventWriter<AppExit>,
 ) {
-    commands.insert_resource(UiFont(asset_server.load("fonts/FiraSans-Bold.ttf")));
-    next_state.set(GameState::MainMenu);
+    let id = campaign_handle.0.id();
+    if asset_server.load_state(id) == LoadState::Failed
+        || asset_server.recursive_dependency_load_state(id) == RecursiveDependencyLoadState::Failed
+    {
+        error!("Failed to load campaign {}", CAMPAIGN_PATH);
+        app_exit_writer.send(AppExit);
+    } else if asset_server.is_loaded_with_dependencies(id) {
+        next_state.set(GameState::MainMenu);
+    }
 }
@@ -0,0 +1,274 @@
+use bevy::prelude::*;
+
+use crate::bindings::{Action, Bindings, KeyNames};
+use crate::state::GameState;
+use crate::ui::assetloader::UiFont;
+
+pub struct ControlsMenuPlugin;
+
+impl Plugin for ControlsMenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Rebinding>()
+            .add_systems(
+                OnEnter(GameState::Controls),
+                ControlsMenuPlugin::spawn_controls_menu,
+            )
+            .add_systems(
+                OnExit(GameState::Controls),
+                ControlsMenuPlugin::despawn_controls_menu,
+            )
+            .add_systems(
+                Update,
+                (
+                    ControlsMenuPlugin::button_interaction,
+                    ControlsMenuPlugin::capture_key,
+                    ControlsMenuPlugin::update_labels.run_if(
+                        resource_changed::<Bindings>.or_else(resource_changed::<Rebinding>),
+                    ),
+                )
+                    .chain()
+                    .run_if(in_state(GameState::Controls)),
+            );
+    }
+}
+
+// Action waiting for its new key, and what happened to the last attempt
+#[derive(Resource, Default)]
+struct Rebinding {
+    action: Option<Action>,
+    message: String,
+}
+
+impl Rebinding {
+    fn label(&self, bindings: &Bindings, key_names: &KeyNames, action: Action) -> String {
+        if self.action == Some(action) {
+            return String::from("Press a key");
+        }
+        bindings
+            .key(action)
+            .map_or_else(|| String::from("Unbound"), |key| key_names.name(key))
+    }
+}
+
+#[derive(Component)]
+struct ControlsMenu;
+
+#[derive(Component)]
+struct BindButton(Action);
+
+#[derive(Component)]
+struct BindingText(Action);
+
+#[derive(Component)]
+struct StatusText;
+
+#[derive(Component)]
+struct DefaultsButton;
+
+#[derive(Component)]
+struct BackButton;
+
+impl ControlsMenuPlugin {
+    fn spawn_controls_menu(
+        mut commands: Commands,
+        font_handle_res: Res<UiFont>,
+        bindings: Res<Bindings>,
+        key_names: Res<KeyNames>,
+        mut rebinding: ResMut<Rebinding>,
+    ) {
+        *rebinding = Rebinding::default();
+        let text = |value: String, font_size: f32, color: Color| TextBundle {
+            text: Text {
+                sections: vec![TextSection {
+                    value,
+                    style: TextStyle {
+                        font: font_handle_res.0.clone(),
+                        font_size,
+                        color,
+                    },
+                }],
+                justify: JustifyText::Center,
+                ..default()
+            },
+            ..default()
+        };
+        let button = |width: f32, height: f32| ButtonBundle {
+            style: Style {
+                width: Val::Px(width),
+                height: Val::Px(height),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            background_color: Color::YELLOW_GREEN.into(),
+            ..default()
+        };
+
+        // Main node
+        commands
+            .spawn((
+                ControlsMenu,
+                NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Column,
+                        justify_content: JustifyContent::Center,
+                        align_items: AlignItems::Center,
+                        row_gap: Val::Px(10.0),
+                        width: Val::Percent(100.0),
+                        height: Val::Percent(100.0),
+                        ..default()
+                    },
+                    visibility: Visibility::Visible,
+                    background_color: Color::ANTIQUE_WHITE.into(),
+                    ..default()
+                },
+            ))
+            .with_children(|parent| {
+                parent.spawn(text(String::from("Controls"), 60.0, Color::GRAY));
+                // One row per action: name, then its key
+                for action in Action::ALL {
+                    parent
+                        .spawn(NodeBundle {
+                            style: Style {
+                                flex_direction: FlexDirection::Row,
+                                align_items: AlignItems::Center,
+                                column_gap: Val::Px(10.0),
+                                height: Val::Px(50.0),
+                                ..default()
+                            },
+                            ..default()
+                        })
+                        .with_children(|parent| {
+                            parent.spawn(
+                                text(String::from(action.label()), 30.0, Color::GRAY).with_style(
+                                    Style {
+                                        width: Val::Px(240.0),
+                                        ..default()
+                                    },
+                                ),
+                            );
+                            parent
+                                .spawn((BindButton(action), button(250.0, 50.0)))
+                                .with_children(|parent| {
+                                    parent.spawn((
+                                        BindingText(action),
+                                        text(
+                                            rebinding.label(&bindings, &key_names, action),
+                                            30.0,
+                                            Color::BLUE,
+                                        ),
+                                    ));
+                                });
+                        });
+                }
+                // Not bindable, the end screen reads them as text
+                parent.spawn(text(
+                    String::from("Letters, digits and Backspace type the high score name"),
+                    25.0,
+                    Color::GRAY,
+                ));
+                parent.spawn((StatusText, text(String::new(), 25.0, Color::MAROON)));
+                // Spawn Defaults button
+                parent
+                    .spawn((DefaultsButton, button(300.0, 80.0)))
+                    .with_children(|parent| {
+                        parent.spawn(text(String::from("Defaults"), 40.0, Color::BLUE));
+                    });
+                // Spawn Back button
+                parent
+                    .spawn((BackButton, button(300.0, 80.0)))
+                    .with_children(|parent| {
+                        parent.spawn(text(String::from("Back"), 40.0, Color::BLUE));
+                    });
+            });
+    }
+
+    fn despawn_controls_menu(
+        mut commands: Commands,
+        window_query: Query<Entity, With<ControlsMenu>>,
+    ) {
+        let entity = window_query.get_single().unwrap();
+        commands.entity(entity).despawn_recursive();
+    }
+
+    // Clicking a key starts capturing, clicking it again cancels
+    #[allow(clippy::type_complexity)]
+    fn button_interaction(
+        mut background_query: Query<
+            (
+                &Interaction,
+                &mut BackgroundColor,
+                Option<&BindButton>,
+                Option<&DefaultsButton>,
+                Option<&BackButton>,
+            ),
+            Changed<Interaction>,
+        >,
+        mut rebinding: ResMut<Rebinding>,
+        mut bindings: ResMut<Bindings>,
+        mut next_state: ResMut<NextState<GameState>>,
+    ) {
+        for (interact, mut backgroundcolor, bind_button, is_defaultsbutton, is_backbutton) in
+            &mut background_query
+        {
+            match interact {
+                Interaction::Hovered => *backgroundcolor = Color::ALICE_BLUE.into(),
+                Interaction::Pressed => {
+                    if let Some(bind_button) = bind_button {
+                        if rebinding.action == Some(bind_button.0) {
+                            *rebinding = Rebinding::default();
+                        } else {
+                            rebinding.action = Some(bind_button.0);
+                            rebinding.message =
+                                String::from("Press a key, or click again to cancel");
+                        }
+                    }
+                    if is_defaultsbutton.is_some() {
+                        *bindings = Bindings::default();
+                        *rebinding = Rebinding::default();
+                    }
+                    if is_backbutton.is_some() {
+                        next_state.set(GameState::Settings);
+                    }
+                }
+                Interaction::None => *backgroundcolor = Color::YELLOW_GREEN.into(),
+            }
+        }
+    }
+
+    // Keys taken by another action are refused so no action is left unreachable,
+    // and Confirm cannot take a key that types into the name field
+    fn capture_key(
+        keyboard_input: Res<ButtonInput<KeyCode>>,
+        key_names: Res<KeyNames>,
+        mut rebinding: ResMut<Rebinding>,
+        mut bindings: ResMut<Bindings>,
+    ) {
+        let Some(action) = rebinding.action else {
+            return;
+        };
+        let Some(&key) = keyboard_input.get_just_pressed().next() else {
+            return;
+        };
+        rebinding.action = None;
+        rebinding.message = match bindings.bind(action, key) {
+            Ok(()) => String::new(),
+            Err(error) => format!("{} is {}", key_names.name(key), error),
+        };
+    }
+
+    fn update_labels(
+        mut binding_query: Query<(&mut Text, &BindingText)>,
+        mut status_query: Query<&mut Text, (With<StatusText>, Without<BindingText>)>,
+        bindings: Res<Bindings>,
+        key_names: Res<KeyNames>,
+        rebinding: Res<Rebinding>,
+    ) {
+        for (mut text, binding) in &mut binding_query {
+            text.sections[0].value = rebinding.label(&bindings, &key_names, binding.0);
+        }
+        for mut text in &mut status_query {
+            text.sections[0].value.clone_from(&rebinding.message);
+        }
+    }
+}
@@ -1,8 +1,18 @@
+use bevy::input::keyboard::KeyboardInput;
+use bevy::input::ButtonState;
 use bevy::{app::AppExit, prelude::*};
 
+use crate::bindings::{Action, Actions, Bindings, KeyNames};
+use crate::combo::Combo;
+use crate::highscore::HighScores;
 use crate::state::GameState;
 use crate::ui::scoreboard::GameScore;
 
+// Longest name accepted in the high score table
+const MAX_NAME_LENGTH: usize = 12;
+// Name recorded when the player leaves without typing one
+const DEFAULT_NAME: &str = "Player";
+
 pub struct EndMenuPlugin;
 
 impl Plugin for EndMenuPlugin {
@@ -10,32 +20,68 @@ impl Plugin for EndMenuPlugin {
         // Spawn
         app.add_systems(OnEnter(GameState::EndMenu), spawn_end_menu);
         app.add_systems(OnExit(GameState::EndMenu), despawn_end_menu);
+        app.add_systems(OnExit(GameState::EndMenu), record_unsaved_name);
+        // Quitting from the button or by closing the window skips OnExit
+        app.add_systems(Last, record_unsaved_name.run_if(on_event::<AppExit>()));
         app.add_systems(
             Update,
             button_interaction.run_if(in_state(GameState::EndMenu)),
         );
+        app.add_systems(
+            Update,
+            enter_name.run_if(in_state(GameState::EndMenu).and_then(resource_exists::<NameEntry>)),
+        );
     }
 }
 
 #[derive(Component)]
 struct EndMenu;
 
+// Name being typed for a score that made the high score table
+#[derive(Resource)]
+struct NameEntry {
+    name: String,
+    score: u32,
+    // Keys still down from gameplay, their repeats are not typed into the name
+    held: Vec<KeyCode>,
+}
+
+#[derive(Component)]
+struct NameField;
+
 #[derive(Component)]
 struct Button {
     button_type: ButtonType,
 }
 
 enum ButtonType {
-    RestartButton,
-    MainMenuButton,
-    QuitButton,
+    Restart,
+    MainMenu,
+    Quit,
 }
 
+#[allow(clippy::too_many_arguments)]
 fn spawn_end_menu(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
     game_score: Res<GameScore>,
+    combo: Res<Combo>,
+    high_scores: Res<HighScores>,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    bindings: Res<Bindings>,
+    key_names: Res<KeyNames>,
+    mut character_events: ResMut<Events<ReceivedCharacter>>,
 ) {
+    let qualifies = high_scores.qualifies(game_score.score);
+    if qualifies {
+        commands.insert_resource(NameEntry {
+            name: String::new(),
+            score: game_score.score,
+            held: keyboard_input.get_pressed().copied().collect(),
+        });
+    }
+    // Characters typed during gameplay are not part of the name
+    character_events.clear();
     // Main node
     commands
         .spawn((
@@ -87,12 +133,86 @@ fn spawn_end_menu(
                         },
                         ..default()
                     });
+                    parent.spawn(TextBundle {
+                        text: Text {
+                            sections: vec![TextSection {
+                                value: String::from("Best Combo: ") + &combo.best.to_string(),
+                                style: TextStyle {
+                                    font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+                                    font_size: 40.0,
+                                    color: Color::GRAY,
+                                },
+                            }],
+                            justify: JustifyText::Center,
+                            ..default()
+                        },
+                        ..default()
+                    });
+                    if !qualifies {
+                        return;
+                    }
+                    let confirm_key = bindings
+                        .key(Action::MenuConfirm)
+                        .map_or_else(|| String::from("Confirm"), |key| key_names.name(key));
+                    parent.spawn(TextBundle {
+                        text: Text {
+                            sections: vec![TextSection {
+                                value: format!(
+                                    "New high score! Type your name, then {}",
+                                    confirm_key
+                                ),
+                                style: TextStyle {
+                                    font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+                                    font_size: 30.0,
+                                    color: Color::GRAY,
+                                },
+                            }],
+                            justify: JustifyText::Center,
+                            ..default()
+                        },
+                        ..default()
+                    });
+                    // Name text field
+                    parent
+                        .spawn(NodeBundle {
+                            style: Style {
+                                width: Val::Px(400.0),
+                                height: Val::Px(60.0),
+                                justify_content: JustifyContent::Center,
+                                align_items: AlignItems::Center,
+                                border: UiRect::all(Val::Px(2.0)),
+                                ..default()
+                            },
+                            border_color: Color::GRAY.into(),
+                            background_color: Color::WHITE.into(),
+                            ..default()
+                        })
+                        .with_children(|parent| {
+                            parent.spawn((
+                                NameField,
+                                TextBundle {
+                                    text: Text {
+                                        sections: vec![TextSection {
+                                            value: String::from("_"),
+                                            style: TextStyle {
+                                                font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+                                                font_size: 40.0,
+                                                color: Color::BLUE,
+                                            },
+                                        }],
+                                        justify: JustifyText::Center,
+                                        ..default()
+                                    },
+                                    ..default()
+                                },
+                            ));
+                        });
                 });
             // Spawn Restart button
             parent
                 .spawn((
                     Button {
-                        button_type: ButtonType::RestartButton,
+                        button_type: ButtonType::Restart,
                     },
                     ButtonBundle {
                         style: Style {
@@ -127,7 +247,7 @@ fn spawn_end_menu(
             parent
                 .spawn((
                     Button {
-                        button_type: ButtonType::MainMenuButton,
+                        button_type: ButtonType::MainMenu,
                     },
                     ButtonBundle {
                         style: Style {
@@ -162,7 +282,7 @@ fn spawn_end_menu(
             parent
                 .spawn((
                     Button {
-                        button_type: ButtonType::QuitButton,
+                        button_type: ButtonType::Quit,
                     },
                     ButtonBundle {
                         style: Style {
@@ -201,6 +321,68 @@ fn despawn_end_menu(mut commands: Commands, window_query: Query<Entity, With<End
     commands.entity(entity).despawn_recursive();
 }
 
+// Type into the name field, Confirm puts the score in the table
+#[allow(clippy::too_many_arguments)]
+fn enter_name(
+    mut commands: Commands,
+    mut name_entry: ResMut<NameEntry>,
+    mut character_events: EventReader<ReceivedCharacter>,
+    mut keyboard_events: EventReader<KeyboardInput>,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    actions: Actions,
+    mut field_query: Query<&mut Text, With<NameField>>,
+    mut high_scores: ResMut<HighScores>,
+) {
+    // Only type characters from keys pressed since the screen opened
+    let typed = keyboard_events.read().any(|event| {
+        event.state == ButtonState::Pressed && !name_entry.held.contains(&event.key_code)
+    });
+    name_entry.held.retain(|key| keyboard_input.pressed(*key));
+    for event in character_events.read() {
+        if !typed {
+            continue;
+        }
+        for character in event.char.chars() {
+            if !character.is_control() && name_entry.name.chars().count() < MAX_NAME_LENGTH {
+                name_entry.name.push(character);
+            }
+        }
+    }
+    if keyboard_input.any_just_pressed([KeyCode::Backspace, KeyCode::NumpadBackspace]) {
+        name_entry.name.pop();
+    }
+    let mut shown = name_entry.name.clone() + "_";
+    if actions.just_pressed(Action::MenuConfirm) {
+        if let Some(rank) = record_name(&mut high_scores, &name_entry) {
+            shown = format!("Saved in place {}", rank);
+        }
+        commands.remove_resource::<NameEntry>();
+    }
+    for mut text in &mut field_query {
+        text.sections[0].value.clone_from(&shown);
+    }
+}
+
+// Leaving the end screen or quitting keeps a qualifying score even if no name was confirmed
+fn record_unsaved_name(
+    mut commands: Commands,
+    name_entry: Option<Res<NameEntry>>,
+    mut high_scores: ResMut<HighScores>,
+) {
+    if let Some(name_entry) = name_entry {
+        record_name(&mut high_scores, &name_entry);
+        commands.remove_resource::<NameEntry>();
+    }
+}
+
+fn record_name(high_scores: &mut HighScores, name_entry: &NameEntry) -> Option<usize> {
+    let name = match name_entry.name.trim() {
+        "" => DEFAULT_NAME,
+        name => name,
+    };
+    high_scores.record(String::from(name), name_entry.score)
+}
+
 fn button_interaction(
     mut background_query: Query<
         (&Interaction, &mut BackgroundColor, &Button),
@@ -213,9 +395,9 @@ fn button_interaction(
         match interact {
             Interaction::Hovered => *backgroundcolor = Color::ALICE_BLUE.into(),
             Interaction::Pressed => match button.button_type {
-                ButtonType::RestartButton => next_state.set(GameState::PrepGame),
-                ButtonType::MainMenuButton => next_state.set(GameState::MainMenu),
-                ButtonType::QuitButton => {
+                ButtonType::Restart => next_state.set(GameState::PrepGame),
+                ButtonType::MainMenu => next_state.set(GameState::MainMenu),
+                ButtonType::Quit => {
                     app_exit_writer.send(AppExit);
                 }
             },
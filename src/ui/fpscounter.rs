@@ -0,0 +1,107 @@
+use bevy::diagnostic::{DiagnosticsStore, FrameTimeDiagnosticsPlugin};
+use bevy::prelude::*;
+
+use crate::settings::Settings;
+use crate::state::GameState;
+use crate::ui::assetloader::UiFont;
+
+// Seconds between two refreshes of the counter
+const REFRESH_INTERVAL: f32 = 0.25;
+
+pub struct FpsCounterPlugin;
+
+impl Plugin for FpsCounterPlugin {
+    fn build(&self, app: &mut App) {
+        // The stress scene may have added it already
+        if !app.is_plugin_added::<FrameTimeDiagnosticsPlugin>() {
+            app.add_plugins(FrameTimeDiagnosticsPlugin);
+        }
+        app.insert_resource(FpsRefresh(Timer::from_seconds(
+            REFRESH_INTERVAL,
+            TimerMode::Repeating,
+        )))
+        .add_systems(
+            OnExit(GameState::LoadAsset),
+            FpsCounterPlugin::spawn_counter,
+        )
+        .add_systems(
+            Update,
+            (
+                FpsCounterPlugin::show_counter.run_if(resource_changed::<Settings>),
+                FpsCounterPlugin::update_counter,
+            ),
+        );
+    }
+}
+
+#[derive(Component)]
+struct FpsCounter;
+
+#[derive(Resource)]
+struct FpsRefresh(Timer);
+
+impl FpsCounterPlugin {
+    fn spawn_counter(
+        mut commands: Commands,
+        font_handle_res: Res<UiFont>,
+        settings: Res<Settings>,
+    ) {
+        commands.spawn((
+            FpsCounter,
+            TextBundle {
+                text: Text::from_section(
+                    "FPS: -",
+                    TextStyle {
+                        font: font_handle_res.0.clone(),
+                        font_size: 20.0,
+                        color: Color::BLACK,
+                    },
+                ),
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    right: Val::Px(8.0),
+                    bottom: Val::Px(4.0),
+                    ..default()
+                },
+                visibility: FpsCounterPlugin::visibility(&settings),
+                z_index: ZIndex::Global(1),
+                ..default()
+            },
+        ));
+    }
+
+    fn visibility(settings: &Settings) -> Visibility {
+        if settings.show_fps {
+            Visibility::Visible
+        } else {
+            Visibility::Hidden
+        }
+    }
+
+    fn show_counter(mut query: Query<&mut Visibility, With<FpsCounter>>, settings: Res<Settings>) {
+        for mut visibility in &mut query {
+            *visibility = FpsCounterPlugin::visibility(&settings);
+        }
+    }
+
+    fn update_counter(
+        mut query: Query<&mut Text, With<FpsCounter>>,
+        mut refresh: ResMut<FpsRefresh>,
+        diagnostics: Res<DiagnosticsStore>,
+        settings: Res<Settings>,
+        time: Res<Time>,
+    ) {
+        if !refresh.0.tick(time.delta()).just_finished() || !settings.show_fps {
+            return;
+        }
+        let Some(fps) = diagnostics
+            .get(&FrameTimeDiagnosticsPlugin::FPS)
+            .and_then(|fps| fps.smoothed())
+        else {
+            return;
+        };
+        for mut text in &mut query {
+            text.sections[0].value = format!("FPS: {:.0}", fps);
+        }
+    }
+}
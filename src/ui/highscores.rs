@@ -0,0 +1,160 @@
+use bevy::prelude::*;
+
+use crate::highscore::HighScores;
+use crate::state::GameState;
+use crate::ui::assetloader::UiFont;
+
+pub struct HighScoresPlugin;
+
+impl Plugin for HighScoresPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            OnEnter(GameState::HighScores),
+            HighScoresPlugin::spawn_high_scores,
+        )
+        .add_systems(
+            OnExit(GameState::HighScores),
+            HighScoresPlugin::despawn_high_scores,
+        )
+        .add_systems(
+            Update,
+            HighScoresPlugin::button_interaction.run_if(in_state(GameState::HighScores)),
+        );
+    }
+}
+
+#[derive(Component)]
+struct HighScoresScreen;
+
+#[derive(Component)]
+struct BackButton;
+
+impl HighScoresPlugin {
+    fn spawn_high_scores(
+        mut commands: Commands,
+        font_handle_res: Res<UiFont>,
+        high_scores: Res<HighScores>,
+    ) {
+        let mut lines: Vec<String> = high_scores
+            .entries
+            .iter()
+            .enumerate()
+            .map(|(index, entry)| format!("{}. {}  {}", index + 1, entry.name, entry.score))
+            .collect();
+        if lines.is_empty() {
+            lines.push(String::from("No high scores yet"));
+        }
+
+        // Main node
+        commands
+            .spawn((
+                HighScoresScreen,
+                NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Column,
+                        justify_content: JustifyContent::Center,
+                        align_items: AlignItems::Center,
+                        row_gap: Val::Px(10.0),
+                        width: Val::Percent(100.0),
+                        height: Val::Percent(100.0),
+                        ..default()
+                    },
+                    visibility: Visibility::Visible,
+                    background_color: Color::ANTIQUE_WHITE.into(),
+                    ..default()
+                },
+            ))
+            .with_children(|parent| {
+                parent.spawn(TextBundle {
+                    text: Text {
+                        sections: vec![TextSection {
+                            value: String::from("High Scores"),
+                            style: TextStyle {
+                                font: font_handle_res.0.clone(),
+                                font_size: 80.0,
+                                color: Color::GRAY,
+                            },
+                        }],
+                        justify: JustifyText::Center,
+                        ..default()
+                    },
+                    ..default()
+                });
+                for value in lines {
+                    parent.spawn(TextBundle {
+                        text: Text {
+                            sections: vec![TextSection {
+                                value,
+                                style: TextStyle {
+                                    font: font_handle_res.0.clone(),
+                                    font_size: 30.0,
+                                    color: Color::BLUE,
+                                },
+                            }],
+                            justify: JustifyText::Center,
+                            ..default()
+                        },
+                        ..default()
+                    });
+                }
+                // Spawn Back button
+                parent
+                    .spawn((
+                        BackButton,
+                        ButtonBundle {
+                            style: Style {
+                                width: Val::Px(300.0),
+                                height: Val::Px(100.0),
+                                justify_content: JustifyContent::Center,
+                                align_items: AlignItems::Center,
+                                ..default()
+                            },
+                            background_color: Color::YELLOW_GREEN.into(),
+                            ..default()
+                        },
+                    ))
+                    .with_children(|parent| {
+                        parent.spawn(TextBundle {
+                            text: Text {
+                                sections: vec![TextSection {
+                                    value: String::from("Back"),
+                                    style: TextStyle {
+                                        font: font_handle_res.0.clone(),
+                                        font_size: 40.0,
+                                        color: Color::BLUE,
+                                    },
+                                }],
+                                justify: JustifyText::Center,
+                                ..default()
+                            },
+                            ..default()
+                        });
+                    });
+            });
+    }
+
+    fn despawn_high_scores(
+        mut commands: Commands,
+        window_query: Query<Entity, With<HighScoresScreen>>,
+    ) {
+        let entity = window_query.get_single().unwrap();
+        commands.entity(entity).despawn_recursive();
+    }
+
+    #[allow(clippy::type_complexity)]
+    fn button_interaction(
+        mut background_query: Query<
+            (&Interaction, &mut BackgroundColor),
+            (Changed<Interaction>, With<BackButton>),
+        >,
+        mut next_state: ResMut<NextState<GameState>>,
+    ) {
+        for (interact, mut backgroundcolor) in &mut background_query {
+            match interact {
+                Interaction::Hovered => *backgroundcolor = Color::ALICE_BLUE.into(),
+                Interaction::Pressed => next_state.set(GameState::MainMenu),
+                Interaction::None => *backgroundcolor = Color::YELLOW_GREEN.into(),
+            }
+        }
+    }
+}
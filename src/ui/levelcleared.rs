@@ -0,0 +1,130 @@
+use bevy::prelude::*;
+
+use crate::level::{Campaign, CampaignHandle, CurrentLevel, Level};
+use crate::state::GameState;
+use crate::ui::assetloader::UiFont;
+use crate::ui::scoreboard::GameScore;
+
+// Seconds the results card stays up before the next level starts
+const NEXT_LEVEL_DELAY: f32 = 2.5;
+
+pub struct LevelClearedPlugin;
+
+impl Plugin for LevelClearedPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            OnEnter(GameState::LevelCleared),
+            LevelClearedPlugin::spawn_results_card,
+        )
+        .add_systems(
+            OnExit(GameState::LevelCleared),
+            LevelClearedPlugin::despawn_results_card,
+        )
+        .add_systems(
+            Update,
+            LevelClearedPlugin::tick_next_level.run_if(in_state(GameState::LevelCleared)),
+        );
+    }
+}
+
+#[derive(Component)]
+struct ResultsCard;
+
+#[derive(Resource)]
+struct NextLevelTimer(Timer);
+
+impl LevelClearedPlugin {
+    fn spawn_results_card(
+        mut commands: Commands,
+        font_handle_res: Res<UiFont>,
+        game_score: Res<GameScore>,
+        campaign_handle: Res<CampaignHandle>,
+        campaigns: Res<Assets<Campaign>>,
+        levels: Res<Assets<Level>>,
+        current_level: Res<CurrentLevel>,
+    ) {
+        commands.insert_resource(NextLevelTimer(Timer::from_seconds(
+            NEXT_LEVEL_DELAY,
+            TimerMode::Once,
+        )));
+
+        let level_name = |index: usize| {
+            campaigns
+                .get(&campaign_handle.0)
+                .and_then(|campaign| campaign.level(index))
+                .and_then(|handle| levels.get(handle))
+                .map_or(String::new(), |level| level.name.clone())
+        };
+        let lines = [
+            (level_name(current_level.0) + " cleared!", 80.0, Color::GRAY),
+            (
+                String::from("Score: ") + &game_score.score.to_string(),
+                40.0,
+                Color::BLUE,
+            ),
+            (
+                String::from("Next up: ") + &level_name(current_level.0 + 1),
+                40.0,
+                Color::BLUE,
+            ),
+        ];
+
+        // Main node
+        commands
+            .spawn((
+                ResultsCard,
+                NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Column,
+                        justify_content: JustifyContent::Center,
+                        align_items: AlignItems::Center,
+                        row_gap: Val::Px(10.0),
+                        width: Val::Percent(100.0),
+                        height: Val::Percent(100.0),
+                        ..default()
+                    },
+                    visibility: Visibility::Visible,
+                    background_color: Color::rgba(0.98, 0.92, 0.84, 0.3).into(),
+                    ..default()
+                },
+            ))
+            .with_children(|parent| {
+                for (value, font_size, color) in lines {
+                    parent.spawn(TextBundle {
+                        text: Text {
+                            sections: vec![TextSection {
+                                value,
+                                style: TextStyle {
+                                    font: font_handle_res.0.clone(),
+                                    font_size,
+                                    color,
+                                },
+                            }],
+                            justify: JustifyText::Center,
+                            ..default()
+                        },
+                        ..default()
+                    });
+                }
+            });
+    }
+
+    fn despawn_results_card(
+        mut commands: Commands,
+        window_query: Query<Entity, With<ResultsCard>>,
+    ) {
+        let entity = window_query.get_single().unwrap();
+        commands.entity(entity).despawn_recursive();
+        commands.remove_resource::<NextLevelTimer>();
+    }
+
+    fn tick_next_level(
+        mut timer: ResMut<NextLevelTimer>,
+        time: Res<Time>,
+        mut next_state: ResMut<NextState<GameState>>,
+    ) {
+        if timer.0.tick(time.delta()).just_finished() {
+            next_state.set(GameState::PrepGame);
+        }
+    }
+}
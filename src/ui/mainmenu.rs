@@ -2,6 +2,7 @@ use bevy::{app::AppExit, prelude::*};
 
 use crate::state::GameState;
 use crate::ui::assetloader::UiFont;
+use crate::ui::settingsmenu::SettingsOrigin;
 
 pub struct MainMenuPlugin;
 
@@ -23,6 +24,12 @@ struct MainMenu;
 #[derive(Component)]
 struct PlayButton;
 
+#[derive(Component)]
+struct HighScoresButton;
+
+#[derive(Component)]
+struct SettingsButton;
+
 #[derive(Component)]
 struct QuitButton;
 
@@ -56,7 +63,7 @@ fn spawn_main_menu(mut commands: Commands, font_handle_res: Res<UiFont>) {
                         align_items: AlignItems::Center,
                         row_gap: Val::Px(10.0),
                         width: Val::Percent(100.0),
-                        height: Val::Percent(50.0),
+                        height: Val::Percent(40.0),
                         ..default()
                     },
                     visibility: Visibility::Visible,
@@ -87,7 +94,7 @@ fn spawn_main_menu(mut commands: Commands, font_handle_res: Res<UiFont>) {
                     ButtonBundle {
                         style: Style {
                             width: Val::Px(300.0),
-                            height: Val::Px(100.0),
+                            height: Val::Px(80.0),
                             justify_content: JustifyContent::Center,
                             align_items: AlignItems::Center,
                             ..default()
@@ -113,6 +120,72 @@ fn spawn_main_menu(mut commands: Commands, font_handle_res: Res<UiFont>) {
                         ..default()
                     });
                 });
+            // Spawn High Scores button
+            parent
+                .spawn((
+                    HighScoresButton,
+                    ButtonBundle {
+                        style: Style {
+                            width: Val::Px(300.0),
+                            height: Val::Px(80.0),
+                            justify_content: JustifyContent::Center,
+                            align_items: AlignItems::Center,
+                            ..default()
+                        },
+                        background_color: Color::YELLOW_GREEN.into(),
+                        ..default()
+                    },
+                ))
+                .with_children(|parent| {
+                    parent.spawn(TextBundle {
+                        text: Text {
+                            sections: vec![TextSection {
+                                value: String::from("High Scores"),
+                                style: TextStyle {
+                                    font: font_handle_res.0.clone(),
+                                    font_size: 40.0,
+                                    color: Color::BLUE,
+                                },
+                            }],
+                            justify: JustifyText::Center,
+                            ..default()
+                        },
+                        ..default()
+                    });
+                });
+            // Spawn Settings button
+            parent
+                .spawn((
+                    SettingsButton,
+                    ButtonBundle {
+                        style: Style {
+                            width: Val::Px(300.0),
+                            height: Val::Px(80.0),
+                            justify_content: JustifyContent::Center,
+                            align_items: AlignItems::Center,
+                            ..default()
+                        },
+                        background_color: Color::YELLOW_GREEN.into(),
+                        ..default()
+                    },
+                ))
+                .with_children(|parent| {
+                    parent.spawn(TextBundle {
+                        text: Text {
+                            sections: vec![TextSection {
+                                value: String::from("Settings"),
+                                style: TextStyle {
+                                    font: font_handle_res.0.clone(),
+                                    font_size: 40.0,
+                                    color: Color::BLUE,
+                                },
+                            }],
+                            justify: JustifyText::Center,
+                            ..default()
+                        },
+                        ..default()
+                    });
+                });
             // Spawn Quit button
             parent
                 .spawn((
@@ -120,7 +193,7 @@ fn spawn_main_menu(mut commands: Commands, font_handle_res: Res<UiFont>) {
                     ButtonBundle {
                         style: Style {
                             width: Val::Px(300.0),
-                            height: Val::Px(100.0),
+                            height: Val::Px(80.0),
                             justify_content: JustifyContent::Center,
                             align_items: AlignItems::Center,
                             ..default()
@@ -154,27 +227,46 @@ fn despawn_main_menu(mut commands: Commands, window_query: Query<Entity, With<Ma
     commands.entity(entity).despawn_recursive();
 }
 
+#[allow(clippy::type_complexity)]
 fn button_interaction(
     mut background_query: Query<
         (
             &Interaction,
             &mut BackgroundColor,
             Option<&PlayButton>,
+            Option<&HighScoresButton>,
+            Option<&SettingsButton>,
             Option<&QuitButton>,
         ),
         Changed<Interaction>,
     >,
     mut next_state: ResMut<NextState<GameState>>,
+    mut settings_origin: ResMut<SettingsOrigin>,
     mut app_exit_writer: EventWriter<AppExit>,
 ) {
-    for (interact, mut backgroundcolor, is_playbutton, is_quitbutton) in &mut background_query {
+    for (
+        interact,
+        mut backgroundcolor,
+        is_playbutton,
+        is_highscoresbutton,
+        is_settingsbutton,
+        is_quitbutton,
+    ) in &mut background_query
+    {
         match interact {
             Interaction::Hovered => *backgroundcolor = Color::ALICE_BLUE.into(),
             Interaction::Pressed => {
-                if let Some(_) = is_playbutton {
+                if is_playbutton.is_some() {
                     next_state.set(GameState::PrepGame);
                 }
-                if let Some(_) = is_quitbutton {
+                if is_highscoresbutton.is_some() {
+                    next_state.set(GameState::HighScores);
+                }
+                if is_settingsbutton.is_some() {
+                    settings_origin.0 = GameState::MainMenu;
+                    next_state.set(GameState::Settings);
+                }
+                if is_quitbutton.is_some() {
                     app_exit_writer.send(AppExit);
                 }
             }
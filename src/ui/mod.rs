@@ -1,5 +1,10 @@
 pub mod assetloader;
+pub mod controlsmenu;
 pub mod endmenu;
+pub mod fpscounter;
+pub mod highscores;
+pub mod levelcleared;
 pub mod mainmenu;
 pub mod pausemenu;
 pub mod scoreboard;
+pub mod settingsmenu;
@@ -2,6 +2,7 @@ use bevy::prelude::*;
 
 use crate::state::GameState;
 use crate::ui::assetloader::UiFont;
+use crate::ui::settingsmenu::SettingsOrigin;
 
 pub struct PauseMenuPlugin;
 
@@ -29,6 +30,9 @@ struct PauseMenu;
 #[derive(Component)]
 struct ContinueButton;
 
+#[derive(Component)]
+struct SettingsButton;
+
 #[derive(Component)]
 struct QuitToMainButton;
 
@@ -87,6 +91,39 @@ impl PauseMenuPlugin {
                             ..default()
                         });
                     });
+                // Spawn Settings button
+                parent
+                    .spawn((
+                        SettingsButton,
+                        ButtonBundle {
+                            style: Style {
+                                width: Val::Px(300.0),
+                                height: Val::Px(100.0),
+                                justify_content: JustifyContent::Center,
+                                align_items: AlignItems::Center,
+                                ..default()
+                            },
+                            background_color: Color::YELLOW_GREEN.into(),
+                            ..default()
+                        },
+                    ))
+                    .with_children(|parent| {
+                        parent.spawn(TextBundle {
+                            text: Text {
+                                sections: vec![TextSection {
+                                    value: String::from("Settings"),
+                                    style: TextStyle {
+                                        font: font_handle_res.0.clone(),
+                                        font_size: 40.0,
+                                        color: Color::BLUE,
+                                    },
+                                }],
+                                justify: JustifyText::Center,
+                                ..default()
+                            },
+                            ..default()
+                        });
+                    });
                 // Spawn Quit button
                 parent
                     .spawn((
@@ -128,27 +165,35 @@ impl PauseMenuPlugin {
         commands.entity(entity).despawn_recursive();
     }
 
+    #[allow(clippy::type_complexity)]
     fn button_interaction(
         mut background_query: Query<
             (
                 &Interaction,
                 &mut BackgroundColor,
                 Option<&ContinueButton>,
+                Option<&SettingsButton>,
                 Option<&QuitToMainButton>,
             ),
             Changed<Interaction>,
         >,
         mut next_state: ResMut<NextState<GameState>>,
+        mut settings_origin: ResMut<SettingsOrigin>,
     ) {
-        for (interact, mut backgroundcolor, is_resumebutton, is_quitbutton) in &mut background_query
+        for (interact, mut backgroundcolor, is_resumebutton, is_settingsbutton, is_quitbutton) in
+            &mut background_query
         {
             match interact {
                 Interaction::Hovered => *backgroundcolor = Color::ALICE_BLUE.into(),
                 Interaction::Pressed => {
-                    if let Some(_) = is_resumebutton {
+                    if is_resumebutton.is_some() {
                         next_state.set(GameState::InGame)
                     }
-                    if let Some(_) = is_quitbutton {
+                    if is_settingsbutton.is_some() {
+                        settings_origin.0 = GameState::Paused;
+                        next_state.set(GameState::Settings)
+                    }
+                    if is_quitbutton.is_some() {
                         next_state.set(GameState::MainMenu)
                     }
                 }
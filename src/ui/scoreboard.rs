@@ -1,6 +1,10 @@
 use bevy::prelude::*;
 
+use crate::combo::Combo;
 use crate::events::UpdateScoreEvent;
+use crate::highscore::HighScores;
+use crate::lives::Lives;
+use crate::powerup::ActiveEffects;
 use crate::state::GameState;
 use crate::ui::assetloader::UiFont;
 use crate::wall;
@@ -16,6 +20,18 @@ pub struct GameScore {
 #[derive(Component)]
 struct ScoreBoard;
 
+#[derive(Component)]
+struct BestBoard;
+
+#[derive(Component)]
+struct LivesBoard;
+
+#[derive(Component)]
+struct ComboBoard;
+
+#[derive(Component)]
+struct ScoreBoardNode;
+
 pub struct ScoreBoardPlugin;
 
 impl Plugin for ScoreBoardPlugin {
@@ -23,14 +39,44 @@ impl Plugin for ScoreBoardPlugin {
         app.insert_resource(GameScore { score: 0 })
             .add_systems(
                 OnEnter(GameState::PrepGame),
+                ScoreBoardPlugin::spawn_scoreboard,
+            )
+            // Score carries over between levels, only a new run resets it
+            .add_systems(
+                OnEnter(GameState::MainMenu),
+                (
+                    ScoreBoardPlugin::despawn_scoreboard,
+                    ScoreBoardPlugin::reset_score,
+                ),
+            )
+            .add_systems(
+                OnExit(GameState::EndMenu),
                 (
-                    ScoreBoardPlugin::spawn_scoreboard,
+                    ScoreBoardPlugin::despawn_scoreboard,
                     ScoreBoardPlugin::reset_score,
                 ),
             )
+            .add_systems(
+                OnExit(GameState::LevelCleared),
+                ScoreBoardPlugin::despawn_scoreboard,
+            )
             .add_systems(
                 Update,
                 ScoreBoardPlugin::update_scoreboard.run_if(on_event::<UpdateScoreEvent>()),
+            )
+            .add_systems(
+                Update,
+                ScoreBoardPlugin::update_bestboard
+                    .after(ScoreBoardPlugin::update_scoreboard)
+                    .run_if(resource_changed::<GameScore>),
+            )
+            .add_systems(
+                Update,
+                ScoreBoardPlugin::update_livesboard.run_if(resource_changed::<Lives>),
+            )
+            .add_systems(
+                Update,
+                ScoreBoardPlugin::update_comboboard.run_if(resource_changed::<Combo>),
             );
         // .add_systems(Update, ScoreBoardPlugin::update_scoreboard.run_if(on_event()));
     }
@@ -45,10 +91,11 @@ impl ScoreBoardPlugin {
         mut board_query: Query<&mut Text, With<ScoreBoard>>,
         mut game_score: ResMut<GameScore>,
         mut update_event: EventReader<UpdateScoreEvent>,
+        active_effects: Res<ActiveEffects>,
         font_handle_res: Res<UiFont>,
     ) {
         for event in update_event.read() {
-            game_score.score += event.score;
+            game_score.score += event.score * event.multiplier * active_effects.score_multiplier();
         }
         for mut text in &mut board_query {
             text.sections = vec![TextSection {
@@ -62,24 +109,98 @@ impl ScoreBoardPlugin {
         }
     }
 
+    // Best of the high score table, or this run once it gets ahead
+    fn update_bestboard(
+        mut board_query: Query<&mut Text, With<BestBoard>>,
+        game_score: Res<GameScore>,
+        high_scores: Res<HighScores>,
+        font_handle_res: Res<UiFont>,
+    ) {
+        for mut text in &mut board_query {
+            text.sections = vec![TextSection {
+                value: ScoreBoardPlugin::best_text(&game_score, &high_scores),
+                style: TextStyle {
+                    font: font_handle_res.0.clone(),
+                    font_size: 30.0,
+                    color: Color::ANTIQUE_WHITE,
+                },
+            }];
+        }
+    }
+
+    fn best_text(game_score: &GameScore, high_scores: &HighScores) -> String {
+        String::from("Best: ") + &high_scores.best().max(game_score.score).to_string()
+    }
+
+    fn update_livesboard(
+        mut board_query: Query<&mut Text, With<LivesBoard>>,
+        lives: Res<Lives>,
+        font_handle_res: Res<UiFont>,
+    ) {
+        for mut text in &mut board_query {
+            text.sections = vec![TextSection {
+                value: String::from("Lives: ") + &lives.remaining.to_string(),
+                style: TextStyle {
+                    font: font_handle_res.0.clone(),
+                    font_size: 30.0,
+                    color: Color::ANTIQUE_WHITE,
+                },
+            }];
+        }
+    }
+
+    fn update_comboboard(
+        mut board_query: Query<&mut Text, With<ComboBoard>>,
+        combo: Res<Combo>,
+        font_handle_res: Res<UiFont>,
+    ) {
+        for mut text in &mut board_query {
+            text.sections = vec![TextSection {
+                value: ScoreBoardPlugin::combo_text(&combo),
+                style: TextStyle {
+                    font: font_handle_res.0.clone(),
+                    font_size: 30.0,
+                    color: Color::ANTIQUE_WHITE,
+                },
+            }];
+        }
+    }
+
+    fn combo_text(combo: &Combo) -> String {
+        format!("Combo: {} x{}", combo.hits, combo.multiplier())
+    }
+
+    fn despawn_scoreboard(mut commands: Commands, query: Query<Entity, With<ScoreBoardNode>>) {
+        for entity in &query {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+
     fn spawn_scoreboard(
         mut commands: Commands,
         font_handle_res: Res<UiFont>,
+        game_score: Res<GameScore>,
+        lives: Res<Lives>,
+        combo: Res<Combo>,
+        high_scores: Res<HighScores>,
     ) {
         // Window Node
         commands
-            .spawn(NodeBundle {
-                style: Style {
-                    justify_content: JustifyContent::Center,
-                    align_items: AlignItems::Center,
-                    width: Val::Percent(100.0),
-                    height: Val::Percent(100.0),
+            .spawn((
+                ScoreBoardNode,
+                NodeBundle {
+                    style: Style {
+                        justify_content: JustifyContent::Center,
+                        align_items: AlignItems::Center,
+                        width: Val::Percent(100.0),
+                        height: Val::Percent(100.0),
+                        ..default()
+                    },
+                    visibility: Visibility::Visible,
+                    background_color: Color::NONE.into(),
                     ..default()
                 },
-                visibility: Visibility::Visible,
-                background_color: Color::NONE.into(),
-                ..default()
-            })
+            ))
             .with_children(|parent| {
                 // Viewport Node
                 parent
@@ -108,6 +229,7 @@ impl ScoreBoardPlugin {
                                 flex_direction: FlexDirection::Row,
                                 justify_content: JustifyContent::Center,
                                 align_items: AlignItems::Center,
+                                column_gap: Val::Px(30.0),
                                 width: Val::Px(SCOREBOARD_WIDTH),
                                 height: Val::Px(SCOREBOARD_HEIGHT),
                                 ..default()
@@ -121,7 +243,8 @@ impl ScoreBoardPlugin {
                                 TextBundle {
                                     text: Text {
                                         sections: vec![TextSection {
-                                            value: String::from("Score:0"),
+                                            value: String::from("Score: ")
+                                                + &game_score.score.to_string(),
                                             style: TextStyle {
                                                 font: font_handle_res.0.clone(),
                                                 font_size: 30.0,
@@ -134,6 +257,64 @@ impl ScoreBoardPlugin {
                                     ..default()
                                 }
                             ));
+                            parent.spawn((
+                                BestBoard,
+                                TextBundle {
+                                    text: Text {
+                                        sections: vec![TextSection {
+                                            value: ScoreBoardPlugin::best_text(
+                                                &game_score,
+                                                &high_scores,
+                                            ),
+                                            style: TextStyle {
+                                                font: font_handle_res.0.clone(),
+                                                font_size: 30.0,
+                                                color: Color::ANTIQUE_WHITE,
+                                            },
+                                        }],
+                                        justify: JustifyText::Center,
+                                        ..default()
+                                    },
+                                    ..default()
+                                },
+                            ));
+                            parent.spawn((
+                                LivesBoard,
+                                TextBundle {
+                                    text: Text {
+                                        sections: vec![TextSection {
+                                            value: String::from("Lives: ")
+                                                + &lives.remaining.to_string(),
+                                            style: TextStyle {
+                                                font: font_handle_res.0.clone(),
+                                                font_size: 30.0,
+                                                color: Color::ANTIQUE_WHITE,
+                                            },
+                                        }],
+                                        justify: JustifyText::Center,
+                                        ..default()
+                                    },
+                                    ..default()
+                                },
+                            ));
+                            parent.spawn((
+                                ComboBoard,
+                                TextBundle {
+                                    text: Text {
+                                        sections: vec![TextSection {
+                                            value: ScoreBoardPlugin::combo_text(&combo),
+                                            style: TextStyle {
+                                                font: font_handle_res.0.clone(),
+                                                font_size: 30.0,
+                                                color: Color::ANTIQUE_WHITE,
+                                            },
+                                        }],
+                                        justify: JustifyText::Center,
+                                        ..default()
+                                    },
+                                    ..default()
+                                },
+                            ));
                         });
                     });
             });
@@ -0,0 +1,221 @@
+use bevy::prelude::*;
+
+use crate::settings::{Setting, Settings};
+use crate::state::GameState;
+use crate::ui::assetloader::UiFont;
+
+// Screen to go back to when leaving the settings
+#[derive(Resource)]
+pub struct SettingsOrigin(pub GameState);
+
+impl Default for SettingsOrigin {
+    fn default() -> Self {
+        SettingsOrigin(GameState::MainMenu)
+    }
+}
+
+pub struct SettingsMenuPlugin;
+
+impl Plugin for SettingsMenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SettingsOrigin>()
+            .add_systems(
+                OnEnter(GameState::Settings),
+                SettingsMenuPlugin::spawn_settings_menu,
+            )
+            .add_systems(
+                OnExit(GameState::Settings),
+                SettingsMenuPlugin::despawn_settings_menu,
+            )
+            .add_systems(
+                Update,
+                (
+                    SettingsMenuPlugin::button_interaction,
+                    SettingsMenuPlugin::update_values.run_if(resource_changed::<Settings>),
+                )
+                    .chain()
+                    .run_if(in_state(GameState::Settings)),
+            );
+    }
+}
+
+#[derive(Component)]
+struct SettingsMenu;
+
+// Moves a setting by `steps` notches when pressed
+#[derive(Component)]
+struct AdjustButton {
+    setting: Setting,
+    steps: i32,
+}
+
+#[derive(Component)]
+struct SettingValue(Setting);
+
+#[derive(Component)]
+struct ControlsButton;
+
+#[derive(Component)]
+struct BackButton;
+
+impl SettingsMenuPlugin {
+    fn spawn_settings_menu(
+        mut commands: Commands,
+        font_handle_res: Res<UiFont>,
+        settings: Res<Settings>,
+    ) {
+        let text = |value: String, font_size: f32, color: Color| TextBundle {
+            text: Text {
+                sections: vec![TextSection {
+                    value,
+                    style: TextStyle {
+                        font: font_handle_res.0.clone(),
+                        font_size,
+                        color,
+                    },
+                }],
+                justify: JustifyText::Center,
+                ..default()
+            },
+            ..default()
+        };
+        let button = |width: f32, height: f32| ButtonBundle {
+            style: Style {
+                width: Val::Px(width),
+                height: Val::Px(height),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            background_color: Color::YELLOW_GREEN.into(),
+            ..default()
+        };
+
+        // Main node
+        commands
+            .spawn((
+                SettingsMenu,
+                NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Column,
+                        justify_content: JustifyContent::Center,
+                        align_items: AlignItems::Center,
+                        row_gap: Val::Px(10.0),
+                        width: Val::Percent(100.0),
+                        height: Val::Percent(100.0),
+                        ..default()
+                    },
+                    visibility: Visibility::Visible,
+                    background_color: Color::ANTIQUE_WHITE.into(),
+                    ..default()
+                },
+            ))
+            .with_children(|parent| {
+                parent.spawn(text(String::from("Settings"), 60.0, Color::GRAY));
+                // One row per setting: name, down, value, up
+                for setting in Setting::ALL {
+                    parent
+                        .spawn(NodeBundle {
+                            style: Style {
+                                flex_direction: FlexDirection::Row,
+                                align_items: AlignItems::Center,
+                                column_gap: Val::Px(10.0),
+                                height: Val::Px(50.0),
+                                ..default()
+                            },
+                            ..default()
+                        })
+                        .with_children(|parent| {
+                            parent.spawn(
+                                text(String::from(setting.label()), 30.0, Color::GRAY).with_style(
+                                    Style {
+                                        width: Val::Px(240.0),
+                                        ..default()
+                                    },
+                                ),
+                            );
+                            for (steps, label) in [(-1, "<"), (1, ">")] {
+                                if steps > 0 {
+                                    parent.spawn((
+                                        SettingValue(setting),
+                                        text(settings.describe(setting), 30.0, Color::BLUE)
+                                            .with_style(Style {
+                                                width: Val::Px(140.0),
+                                                ..default()
+                                            }),
+                                    ));
+                                }
+                                parent
+                                    .spawn((AdjustButton { setting, steps }, button(50.0, 50.0)))
+                                    .with_children(|parent| {
+                                        parent.spawn(text(String::from(label), 30.0, Color::BLUE));
+                                    });
+                            }
+                        });
+                }
+                // Spawn Controls button
+                parent
+                    .spawn((ControlsButton, button(300.0, 80.0)))
+                    .with_children(|parent| {
+                        parent.spawn(text(String::from("Controls"), 40.0, Color::BLUE));
+                    });
+                // Spawn Back button
+                parent
+                    .spawn((BackButton, button(300.0, 80.0)))
+                    .with_children(|parent| {
+                        parent.spawn(text(String::from("Back"), 40.0, Color::BLUE));
+                    });
+            });
+    }
+
+    fn despawn_settings_menu(
+        mut commands: Commands,
+        window_query: Query<Entity, With<SettingsMenu>>,
+    ) {
+        let entity = window_query.get_single().unwrap();
+        commands.entity(entity).despawn_recursive();
+    }
+
+    #[allow(clippy::type_complexity)]
+    fn button_interaction(
+        mut background_query: Query<
+            (
+                &Interaction,
+                &mut BackgroundColor,
+                Option<&AdjustButton>,
+                Option<&ControlsButton>,
+                Option<&BackButton>,
+            ),
+            Changed<Interaction>,
+        >,
+        mut settings: ResMut<Settings>,
+        settings_origin: Res<SettingsOrigin>,
+        mut next_state: ResMut<NextState<GameState>>,
+    ) {
+        for (interact, mut backgroundcolor, adjust_button, is_controlsbutton, is_backbutton) in
+            &mut background_query
+        {
+            match interact {
+                Interaction::Hovered => *backgroundcolor = Color::ALICE_BLUE.into(),
+                Interaction::Pressed => {
+                    if let Some(adjust_button) = adjust_button {
+                        settings.adjust(adjust_button.setting, adjust_button.steps);
+                    }
+                    if is_controlsbutton.is_some() {
+                        next_state.set(GameState::Controls);
+                    }
+                    if is_backbutton.is_some() {
+                        next_state.set(settings_origin.0.clone());
+                    }
+                }
+                Interaction::None => *backgroundcolor = Color::YELLOW_GREEN.into(),
+            }
+        }
+    }
+
+    fn update_values(mut value_query: Query<(&mut Text, &SettingValue)>, settings: Res<Settings>) {
+        for (mut text, value) in &mut value_query {
+            text.sections[0].value = settings.describe(value.0);
+        }
+    }
+}
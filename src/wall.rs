@@ -1,4 +1,8 @@
 use crate::collider::Collider;
+use crate::events::{CollisionEvent, CollisionKind, GrantBarrierEvent};
+use crate::paddle::{PADDLE_SIZE, PADDLE_Y_OFFSET};
+use crate::powerup::PowerUpPlugin;
+use crate::schedule::InGameSet;
 use crate::state::GameState;
 use bevy::prelude::*;
 
@@ -11,11 +15,37 @@ pub const BOTTOM_WALL: f32 = -360.0;
 pub const TOP_WALL: f32 = 360.0;
 // Wall color
 const WALL_COLOR: Color = Color::rgb(0.8, 0.8, 0.8);
+const BARRIER_COLOR: Color = Color::rgb(0.3, 0.9, 0.9);
+// The barrier's top is flush with the underside of the paddle, no ball fits between them
+const BARRIER_Y: f32 = PADDLE_Y_OFFSET - PADDLE_SIZE.y / 2.0 - WALL_THICKNESS / 2.0;
+// The barrier starts flashing this many seconds before it runs out, or on its last bounce
+const BARRIER_WARNING: f32 = 3.0;
+// Flashes per second of an expiring barrier
+const BARRIER_FLASH_RATE: f32 = 6.0;
 
 pub enum WallLocation {
     Left,
     Right,
     Top,
+    // Only present while a safety barrier is granted
+    Bottom,
+}
+
+// Temporary bottom wall, gone after its last bounce or when its time is up
+#[derive(Component)]
+pub struct Barrier {
+    bounces_left: u32,
+    timer: Timer,
+}
+
+impl Barrier {
+    fn expiring(&self) -> bool {
+        self.bounces_left <= 1 || self.timer.remaining_secs() <= BARRIER_WARNING
+    }
+
+    fn expired(&self) -> bool {
+        self.bounces_left == 0 || self.timer.finished()
+    }
 }
 
 pub struct WallPlugin;
@@ -24,7 +54,24 @@ impl Plugin for WallPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(OnEnter(GameState::PrepGame), WallPlugin::spawn_wall)
             .add_systems(OnExit(GameState::EndMenu), WallPlugin::despawn_wall)
-            .add_systems(OnEnter(GameState::MainMenu), WallPlugin::despawn_wall);
+            .add_systems(OnExit(GameState::LevelCleared), WallPlugin::despawn_wall)
+            .add_systems(OnEnter(GameState::MainMenu), WallPlugin::despawn_wall)
+            .add_systems(
+                FixedUpdate,
+                WallPlugin::flash_barrier.in_set(InGameSet::EntityUpdates),
+            )
+            .add_systems(
+                FixedUpdate,
+                (
+                    WallPlugin::grant_barrier.after(PowerUpPlugin::collect),
+                    WallPlugin::wear_barrier,
+                )
+                    .in_set(InGameSet::CollisionResponse),
+            )
+            .add_systems(
+                FixedUpdate,
+                WallPlugin::expire_barrier.in_set(InGameSet::DespawnEntities),
+            );
     }
 }
 
@@ -40,6 +87,66 @@ impl WallPlugin {
             commands.entity(wall).despawn();
         }
     }
+
+    // Raise the barrier, or top up the one already there
+    fn grant_barrier(
+        mut commands: Commands,
+        mut grant_events: EventReader<GrantBarrierEvent>,
+        mut barrier_query: Query<&mut Barrier>,
+    ) {
+        for event in grant_events.read() {
+            let barrier = Barrier {
+                bounces_left: event.bounces,
+                timer: Timer::from_seconds(event.seconds, TimerMode::Once),
+            };
+            match barrier_query.get_single_mut() {
+                Ok(mut current) => *current = barrier,
+                Err(_) => {
+                    commands.spawn((
+                        WallBundle::new(WallLocation::Bottom).with_color(BARRIER_COLOR),
+                        barrier,
+                    ));
+                }
+            }
+        }
+    }
+
+    // Every ball bounce uses up the barrier a little
+    fn wear_barrier(
+        mut collision_events: EventReader<CollisionEvent>,
+        mut barrier_query: Query<&mut Barrier>,
+    ) {
+        for event in collision_events.read() {
+            if event.kind != CollisionKind::Wall {
+                continue;
+            }
+            if let Ok(mut barrier) = barrier_query.get_mut(event.other) {
+                barrier.bounces_left = barrier.bounces_left.saturating_sub(1);
+            }
+        }
+    }
+
+    // Blink the barrier once it is about to go
+    fn flash_barrier(mut query: Query<(&mut Barrier, &mut Visibility)>, time: Res<Time>) {
+        for (mut barrier, mut visibility) in &mut query {
+            barrier.timer.tick(time.delta());
+            let flash_off = barrier.expiring()
+                && (barrier.timer.elapsed_secs() * BARRIER_FLASH_RATE) as u32 % 2 == 1;
+            *visibility = if flash_off {
+                Visibility::Hidden
+            } else {
+                Visibility::Visible
+            };
+        }
+    }
+
+    fn expire_barrier(mut commands: Commands, query: Query<(Entity, &Barrier)>) {
+        for (entity, barrier) in &query {
+            if barrier.expired() {
+                commands.entity(entity).despawn();
+            }
+        }
+    }
 }
 
 impl WallLocation {
@@ -61,6 +168,11 @@ impl WallLocation {
                 y: TOP_WALL,
                 z: 0.0,
             },
+            WallLocation::Bottom => Vec3 {
+                x: 0.0,
+                y: BARRIER_Y,
+                z: 0.0,
+            },
         }
     }
     // Return the size of the given wall sprite
@@ -78,7 +190,7 @@ impl WallLocation {
                 y: arena_height,
                 z: 1.0,
             },
-            WallLocation::Top => Vec3 {
+            WallLocation::Top | WallLocation::Bottom => Vec3 {
                 x: arena_width,
                 y: WALL_THICKNESS,
                 z: 1.0,
@@ -112,8 +224,67 @@ impl WallBundle {
                 },
                 ..default()
             },
-            collider: Collider,
+            collider: Collider::aabb(location.size()),
             wall: Wall,
         }
     }
+
+    pub fn with_color(mut self, color: Color) -> WallBundle {
+        self.sprite_bundle.sprite.color = color;
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bevy::ecs::system::RunSystemOnce;
+
+    use super::*;
+    use crate::ball::{Ball, BALL_DIAMETER};
+    use crate::broadphase::{BroadPhase, BroadPhasePlugin};
+    use crate::collider::ColliderPlugin;
+    use crate::paddle::PaddleBundle;
+
+    #[test]
+    fn ball_beside_the_paddle_escapes_over_the_barrier() {
+        let mut world = World::new();
+        world.init_resource::<BroadPhase>();
+        world.init_resource::<Events<CollisionEvent>>();
+        let mut time = Time::<()>::default();
+        time.advance_by(Duration::from_secs_f32(1.0 / 120.0));
+        world.insert_resource(time);
+        world.spawn(PaddleBundle::new());
+        world.spawn(WallBundle::new(WallLocation::Bottom));
+        world.run_system_once(BroadPhasePlugin::sync_broadphase);
+
+        // As low as it can get past the right end of the paddle, heading under it
+        let radius = BALL_DIAMETER / 2.0;
+        let start = Vec3::new(
+            PADDLE_SIZE.x / 2.0 + radius + 1.0,
+            PADDLE_Y_OFFSET + 1.0,
+            0.0,
+        );
+        let ball = world
+            .spawn((
+                Ball {
+                    velocity: Vec2::new(-150.0, -250.0),
+                },
+                Transform::from_translation(start),
+                Collider::Circle { radius },
+            ))
+            .id();
+
+        let paddle_top = PADDLE_Y_OFFSET + PADDLE_SIZE.y / 2.0;
+        for _ in 0..120 {
+            world.run_system_once(ColliderPlugin::move_and_collide);
+            let y = world.get::<Transform>(ball).unwrap().translation.y;
+            assert!(y - radius >= BARRIER_Y + WALL_THICKNESS / 2.0 - 1e-3);
+            if y - radius > paddle_top {
+                return;
+            }
+        }
+        panic!("ball stayed below the top of the paddle");
+    }
 }